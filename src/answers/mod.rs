@@ -0,0 +1,255 @@
+//! Answers that have been submitted before, and whether they were right.
+//!
+//! Each day can have an answers file next to its input.
+//! Every line is `<part> <verdict> <answer>`, and `#` starts a comment:
+//!
+//! ```text
+//! # the site told me these
+//! 1 too-low 245
+//! 1 correct 388
+//! 2 wrong 18038
+//! ```
+//!
+//! Verdicts are `correct`, `wrong`, `too-low` and `too-high`.
+
+use anyhow::{anyhow, bail, Context, Error, Result};
+
+use std::{
+    fmt::{Display, Formatter},
+    fs,
+    str::FromStr,
+};
+
+use crate::{input::InputLoader, solution::Part};
+
+/// What the site said about an answer.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Verdict {
+    Correct,
+    /// Wrong with no hint
+    Wrong,
+    TooLow,
+    TooHigh,
+}
+
+impl Display for Verdict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Verdict::Correct => "correct",
+            Verdict::Wrong => "wrong",
+            Verdict::TooLow => "too low",
+            Verdict::TooHigh => "too high",
+        })
+    }
+}
+
+impl FromStr for Verdict {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "correct" => Verdict::Correct,
+            "wrong" => Verdict::Wrong,
+            "too-low" => Verdict::TooLow,
+            "too-high" => Verdict::TooHigh,
+            oh_no => bail!("unknown verdict `{}`", oh_no),
+        })
+    }
+}
+
+/// One answer that was submitted.
+#[derive(Debug, Clone)]
+pub struct Recorded {
+    pub part: Part,
+    pub verdict: Verdict,
+    pub answer: String,
+}
+
+/// What we know about a new answer.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Check {
+    /// It's the confirmed answer
+    Correct,
+    /// There's a confirmed answer and this isn't it
+    Regression { expected: String },
+    /// It was submitted before and was wrong,
+    /// or it's on the wrong side of a guess that was too low or too high
+    KnownWrong { verdict: Verdict, guess: String },
+    /// Nothing's known about it
+    Unknown,
+}
+
+/// All the answers recorded for one day.
+#[derive(Debug, Clone, Default)]
+pub struct Answers {
+    pub recorded: Vec<Recorded>,
+}
+
+impl Answers {
+    /// Parse an answers file.
+    pub fn parse(i: &str) -> Result<Self> {
+        let recorded = i
+            .lines()
+            .enumerate()
+            .filter_map(|(idx, line)| {
+                // strip comments
+                let line = match line.find('#') {
+                    Some(comment) => &line[..comment],
+                    None => line,
+                };
+                let split = line.split_ascii_whitespace().collect::<Vec<_>>();
+                let recorded = match split.as_slice() {
+                    [] => return None,
+                    [part, verdict, answer] => parse_recorded(part, verdict, answer),
+                    oh_no => Err(anyhow!("expected 3 words, found {}", oh_no.len())),
+                };
+                Some(recorded.with_context(|| format!("on line {}", idx + 1)))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { recorded })
+    }
+
+    /// Load the answers for a day, or None if there's no answers file.
+    pub fn load(loader: &InputLoader, day: u32) -> Result<Option<Self>> {
+        let path = match loader.find_answers(day) {
+            Some(it) => it,
+            None => return Ok(None),
+        };
+        let file = fs::read_to_string(&path)
+            .with_context(|| format!("could not read answers at `{}`", path.display()))?;
+        let answers =
+            Answers::parse(&file).with_context(|| format!("in answers at `{}`", path.display()))?;
+        Ok(Some(answers))
+    }
+
+    /// Get the confirmed answer to a part, if there is one.
+    pub fn correct(&self, part: Part) -> Option<&str> {
+        self.recorded
+            .iter()
+            .find(|rec| rec.part == part && rec.verdict == Verdict::Correct)
+            .map(|rec| rec.answer.as_str())
+    }
+
+    /// Check an answer against what's been recorded.
+    pub fn check(&self, part: Part, answer: &str) -> Check {
+        let answer = answer.trim();
+        if let Some(expected) = self.correct(part) {
+            return if expected == answer {
+                Check::Correct
+            } else {
+                Check::Regression {
+                    expected: expected.to_string(),
+                }
+            };
+        }
+
+        let numeric = answer.parse::<i128>().ok();
+        let known_wrong = self
+            .recorded
+            .iter()
+            .filter(|rec| rec.part == part)
+            .find(|rec| {
+                if rec.answer == answer {
+                    return true;
+                }
+                // a guess that was too low means everything below it is too
+                match (numeric, rec.answer.parse::<i128>(), rec.verdict) {
+                    (Some(answer), Ok(guess), Verdict::TooLow) => answer < guess,
+                    (Some(answer), Ok(guess), Verdict::TooHigh) => answer > guess,
+                    _ => false,
+                }
+            });
+        match known_wrong {
+            Some(rec) => Check::KnownWrong {
+                verdict: rec.verdict,
+                guess: rec.answer.clone(),
+            },
+            None => Check::Unknown,
+        }
+    }
+}
+
+/// Parse the three words of a line of an answers file
+fn parse_recorded(part: &str, verdict: &str, answer: &str) -> Result<Recorded> {
+    Ok(Recorded {
+        part: part.parse()?,
+        verdict: verdict.parse()?,
+        answer: answer.to_string(),
+    })
+}
+
+/// Check an answer a test came up with against the recorded answers for its day.
+///
+/// Errors if it doesn't match the confirmed answer,
+/// and prints a warning if it's known to be wrong.
+/// Does nothing if there's no answers file.
+pub fn check_for_test(day: u32, part: Part, answer: &impl Display) -> Result<()> {
+    let answers = match Answers::load(&InputLoader::from_env(), day)? {
+        Some(it) => it,
+        None => return Ok(()),
+    };
+    match answers.check(part, &answer.to_string()) {
+        Check::Regression { expected } => bail!(
+            "day {} part {}: got {} but the answer is {}",
+            day,
+            part,
+            answer,
+            expected
+        ),
+        Check::KnownWrong { verdict, guess } => println!(
+            "warning: day {} part {}: {} is known to be wrong ({} was {})",
+            day, part, answer, guess, verdict
+        ),
+        Check::Correct | Check::Unknown => {}
+    }
+    Ok(())
+}
+
+/// Check a test's answer with [`check_for_test`], returning from the test if it's a regression.
+///
+/// `check_answer!(17, One, answer)`
+#[cfg(test)]
+macro_rules! check_answer {
+    ($day:expr, $part:ident, $answer:expr) => {
+        $crate::answers::check_for_test($day, $crate::solution::Part::$part, &$answer)?
+    };
+}
+
+#[test]
+fn test_check() -> Result<()> {
+    let answers = Answers::parse(
+        r"# comment
+1 too-low 245 # trailing comment
+1 wrong 300
+
+2 too-high 4029
+2 correct 2000",
+    )?;
+    assert_eq!(answers.check(Part::One, "388"), Check::Unknown);
+    assert_eq!(
+        answers.check(Part::One, "300"),
+        Check::KnownWrong {
+            verdict: Verdict::Wrong,
+            guess: "300".to_string()
+        }
+    );
+    assert_eq!(
+        answers.check(Part::One, "12"),
+        Check::KnownWrong {
+            verdict: Verdict::TooLow,
+            guess: "245".to_string()
+        }
+    );
+    assert_eq!(answers.check(Part::Two, "2000"), Check::Correct);
+    assert_eq!(
+        answers.check(Part::Two, "1999"),
+        Check::Regression {
+            expected: "2000".to_string()
+        }
+    );
+
+    assert!(Answers::parse("1 maybe 12").is_err());
+    assert!(Answers::parse("3 correct 12").is_err());
+    assert!(Answers::parse("1 correct").is_err());
+
+    Ok(())
+}
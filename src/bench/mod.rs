@@ -0,0 +1,247 @@
+//! Timing how long each day takes to parse and solve.
+//!
+//! Reports can be saved to a file and loaded back, so runs from before and after a change
+//! can be compared. The file has a header line and then one tab-separated line per timing:
+//! `day phase runs min_ns median_ns mean_ns`.
+
+use anyhow::{anyhow, bail, Context, Error, Result};
+
+use std::{
+    fmt::{Display, Formatter},
+    fs,
+    path::Path,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    input::ExtraFiles,
+    solution::{Entry, Part},
+};
+
+/// Header line of a saved report
+const HEADER: &str = "# day\tphase\truns\tmin_ns\tmedian_ns\tmean_ns";
+
+/// One thing that gets timed.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Phase {
+    Parse,
+    Solve(Part),
+}
+
+impl Phase {
+    /// Every phase, in the order they run.
+    pub const ALL: [Phase; 3] = [
+        Phase::Parse,
+        Phase::Solve(Part::One),
+        Phase::Solve(Part::Two),
+    ];
+}
+
+impl Display for Phase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Phase::Parse => write!(f, "parse"),
+            Phase::Solve(part) => write!(f, "part{}", part),
+        }
+    }
+}
+
+impl FromStr for Phase {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "parse" => Phase::Parse,
+            "part1" => Phase::Solve(Part::One),
+            "part2" => Phase::Solve(Part::Two),
+            oh_no => bail!("unknown phase `{}`", oh_no),
+        })
+    }
+}
+
+/// How long one phase of one day took over several runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Timing {
+    pub day: u32,
+    pub phase: Phase,
+    pub runs: u32,
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+}
+
+impl Timing {
+    /// Summarize the times from each run
+    fn from_samples(day: u32, phase: Phase, mut samples: Vec<Duration>) -> Self {
+        samples.sort();
+        let runs = samples.len() as u32;
+        let total: Duration = samples.iter().sum();
+        Self {
+            day,
+            phase,
+            runs,
+            min: samples[0],
+            median: samples[samples.len() / 2],
+            mean: total / runs,
+        }
+    }
+}
+
+/// How many times to run everything.
+#[derive(Debug, Clone, Copy)]
+pub struct BenchOptions {
+    /// Run each phase this many times...
+    pub runs: u32,
+    /// ...unless it has already taken this long. Everything runs at least once.
+    pub budget: Duration,
+}
+
+impl Default for BenchOptions {
+    fn default() -> Self {
+        Self {
+            runs: 10,
+            budget: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Run `f` as many times as the options say, recording how long each run took.
+/// Returns the times and the output of the last run.
+fn sample<T>(opts: &BenchOptions, mut f: impl FnMut() -> Result<T>) -> Result<(Vec<Duration>, T)> {
+    let mut samples = Vec::new();
+    let started = Instant::now();
+    loop {
+        let start = Instant::now();
+        let out = f()?;
+        samples.push(start.elapsed());
+
+        if samples.len() as u32 >= opts.runs.max(1) || started.elapsed() >= opts.budget {
+            return Ok((samples, out));
+        }
+    }
+}
+
+/// Benchmark parsing and solving each part of a day.
+/// `extra` is the other files the day needs, which are loaded beforehand so they aren't timed.
+pub fn bench_day(
+    entry: &Entry,
+    input: &str,
+    extra: &ExtraFiles,
+    opts: &BenchOptions,
+) -> Result<Vec<Timing>> {
+    let (samples, parsed) =
+        sample(opts, || entry.parse_with(input, extra)).context("while parsing")?;
+    let mut out = vec![Timing::from_samples(entry.day, Phase::Parse, samples)];
+
+    for &part in Part::ALL.iter() {
+        let (samples, _) = sample(opts, || entry.solve_parsed(&parsed, part))
+            .with_context(|| format!("while solving part {}", part))?;
+        out.push(Timing::from_samples(entry.day, Phase::Solve(part), samples));
+    }
+    Ok(out)
+}
+
+/// All the timings from one benchmark run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Report {
+    pub timings: Vec<Timing>,
+}
+
+impl Report {
+    /// Get the timing for a phase of a day, if it's in here.
+    pub fn get(&self, day: u32, phase: Phase) -> Option<&Timing> {
+        self.timings
+            .iter()
+            .find(|timing| timing.day == day && timing.phase == phase)
+    }
+
+    /// Parse a report from the saved format.
+    pub fn parse(i: &str) -> Result<Self> {
+        let timings = i
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'))
+            .map(|(idx, line)| parse_timing(line).with_context(|| format!("on line {}", idx + 1)))
+            .collect::<Result<_>>()?;
+        Ok(Self { timings })
+    }
+
+    /// Load a saved report.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = fs::read_to_string(path)
+            .with_context(|| format!("could not read report at `{}`", path.display()))?;
+        Report::parse(&file).with_context(|| format!("in report at `{}`", path.display()))
+    }
+
+    /// Save this report to a file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        fs::write(path, self.to_string())
+            .with_context(|| format!("could not write report to `{}`", path.display()))
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", HEADER)?;
+        for timing in self.timings.iter() {
+            writeln!(
+                f,
+                "{}\t{}\t{}\t{}\t{}\t{}",
+                timing.day,
+                timing.phase,
+                timing.runs,
+                timing.min.as_nanos(),
+                timing.median.as_nanos(),
+                timing.mean.as_nanos()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse one line of a saved report
+fn parse_timing(line: &str) -> Result<Timing> {
+    let split = line.split('\t').collect::<Vec<_>>();
+    match split.as_slice() {
+        [day, phase, runs, min, median, mean] => {
+            let nanos = |s: &str| -> Result<Duration> { Ok(Duration::from_nanos(s.parse()?)) };
+            Ok(Timing {
+                day: day.parse()?,
+                phase: phase.parse()?,
+                runs: runs.parse()?,
+                min: nanos(min)?,
+                median: nanos(median)?,
+                mean: nanos(mean)?,
+            })
+        }
+        oh_no => Err(anyhow!("expected 6 columns, found {}", oh_no.len())),
+    }
+}
+
+#[test]
+fn test_round_trip() -> Result<()> {
+    let entry = crate::solution::get(15).unwrap();
+    let opts = BenchOptions {
+        runs: 3,
+        budget: Duration::from_secs(1),
+    };
+    // only part 1 of this is quick, so bench the phases by hand
+    let (samples, parsed) = sample(&opts, || entry.parse("0,3,6"))?;
+    assert_eq!(samples.len(), 3);
+    let (part1, _) = sample(&opts, || entry.solve_parsed(&parsed, Part::One))?;
+
+    let report = Report {
+        timings: vec![
+            Timing::from_samples(15, Phase::Parse, samples),
+            Timing::from_samples(15, Phase::Solve(Part::One), part1),
+        ],
+    };
+    let loaded = Report::parse(&report.to_string())?;
+    assert_eq!(report, loaded);
+    assert!(loaded.get(15, Phase::Solve(Part::One)).is_some());
+    assert!(loaded.get(15, Phase::Solve(Part::Two)).is_none());
+
+    Ok(())
+}
@@ -0,0 +1,377 @@
+//! Run the solutions from the command line.
+//!
+//! ```text
+//! aoc [DAYS] [--part 1|2] [--input-dir DIR] [--input FILE]
+//! aoc [DAYS] --bench [--runs N] [--save FILE] [--baseline FILE] [--input-dir DIR] [--input FILE]
+//! ```
+//!
+//! `DAYS` is `all` (the default), one day like `8`, a range like `3-7`,
+//! or a comma-separated list of those like `1,4-6,9`.
+//!
+//! With `--bench`, each day is parsed and solved several times and the times are reported.
+//! `--save` writes them to a file, and `--baseline` compares against a file saved earlier.
+
+use aoc_2020::{
+    answers::{Answers, Check},
+    bench::{self, BenchOptions, Report, Timing},
+    input::{ExtraFiles, InputLoader},
+    solution::{self, Entry, Part},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+
+use std::{
+    env, fs,
+    path::PathBuf,
+    process,
+    time::{Duration, Instant},
+};
+
+const USAGE: &str = "usage: aoc [DAYS] [--part 1|2] [--input-dir DIR] [--input FILE]
+       aoc [DAYS] --bench [--runs N] [--save FILE] [--baseline FILE] [--input-dir DIR] [--input FILE]
+
+DAYS is `all` (the default), a day like `8`, a range like `3-7`,
+or a comma-separated list of those like `1,4-6,9`.
+
+--bench times parsing and solving each part separately, running each --runs times (default 10).
+--save writes the times to a file, and --baseline compares them against a file saved earlier.";
+
+/// What to run, from the command line.
+struct Args {
+    days: Vec<u32>,
+    /// Only run this part, or both if None
+    part: Option<Part>,
+    /// Where to look for inputs
+    loader: InputLoader,
+    /// Use this file as the input instead of looking for one
+    input: Option<PathBuf>,
+
+    /// Benchmark instead of just solving
+    bench: bool,
+    bench_opts: BenchOptions,
+    /// Save the benchmark report here
+    save: Option<PathBuf>,
+    /// Compare the benchmark against the report here
+    baseline: Option<PathBuf>,
+}
+
+impl Args {
+    /// Parse the arguments (without the program name)
+    fn parse(args: impl Iterator<Item = String>) -> Result<Self> {
+        let mut days = None;
+        let mut part = None;
+        let mut loader = InputLoader::from_env();
+        let mut input = None;
+        let mut bench = false;
+        let mut bench_opts = BenchOptions::default();
+        let mut save = None;
+        let mut baseline = None;
+
+        let mut args = args;
+        while let Some(arg) = args.next() {
+            let mut value = |flag: &str| {
+                args.next()
+                    .ok_or_else(|| anyhow!("`{}` needs a value", flag))
+            };
+            match arg.as_str() {
+                "--part" | "-p" => part = Some(value(&arg)?.parse()?),
+                "--input-dir" | "-d" => loader = InputLoader::new(value(&arg)?),
+                "--input" | "-i" => input = Some(PathBuf::from(value(&arg)?)),
+                "--bench" | "-b" => bench = true,
+                "--runs" | "-n" => bench_opts.runs = value(&arg)?.parse()?,
+                "--save" => save = Some(PathBuf::from(value(&arg)?)),
+                "--baseline" => baseline = Some(PathBuf::from(value(&arg)?)),
+                "--help" | "-h" => {
+                    println!("{}", USAGE);
+                    process::exit(0);
+                }
+                flag if flag.starts_with('-') => bail!("unknown flag `{}`", flag),
+                spec => {
+                    if days.is_some() {
+                        bail!("days were given twice");
+                    }
+                    days = Some(parse_days(spec)?);
+                }
+            }
+        }
+
+        let days = match days {
+            Some(it) => it,
+            None => solution::REGISTRY.iter().map(|entry| entry.day).collect(),
+        };
+        if input.is_some() && days.len() != 1 {
+            bail!("`--input` only works when running one day");
+        }
+        if !bench && (save.is_some() || baseline.is_some()) {
+            bail!("`--save` and `--baseline` only work with `--bench`");
+        }
+        if bench && part.is_some() {
+            bail!("`--bench` always runs both parts");
+        }
+
+        Ok(Self {
+            days,
+            part,
+            loader,
+            input,
+            bench,
+            bench_opts,
+            save,
+            baseline,
+        })
+    }
+}
+
+/// Parse a day spec like `all`, `8`, `3-7` or `1,4-6,9` into a list of days.
+fn parse_days(spec: &str) -> Result<Vec<u32>> {
+    if spec == "all" {
+        return Ok(solution::REGISTRY.iter().map(|entry| entry.day).collect());
+    }
+
+    let mut days = Vec::new();
+    for chunk in spec.split(',') {
+        let (lo, hi) = match chunk.split_once('-') {
+            Some((lo, hi)) => (lo, hi),
+            None => (chunk, chunk),
+        };
+        let lo: u32 = lo
+            .parse()
+            .with_context(|| format!("bad day `{}` in `{}`", lo, spec))?;
+        let hi: u32 = hi
+            .parse()
+            .with_context(|| format!("bad day `{}` in `{}`", hi, spec))?;
+        if lo > hi {
+            bail!("range `{}` goes backwards", chunk);
+        }
+        for day in lo..=hi {
+            if solution::get(day).is_none() {
+                bail!("there's no solution for day {}", day);
+            }
+            if !days.contains(&day) {
+                days.push(day);
+            }
+        }
+    }
+    Ok(days)
+}
+
+/// Format a duration in a unit that makes it readable
+fn format_duration(time: Duration) -> String {
+    let micros = time.as_micros();
+    if micros == 0 {
+        format!("{}ns", time.as_nanos())
+    } else if micros < 1000 {
+        format!("{}µs", micros)
+    } else if micros < 1_000_000 {
+        format!("{:.2}ms", micros as f64 / 1000.0)
+    } else {
+        format!("{:.2}s", time.as_secs_f64())
+    }
+}
+
+/// Load the input for a day, and the other files it needs.
+///
+/// The other files always come from the input directory, even with `--input`.
+fn load_input(entry: &Entry, args: &Args) -> Result<(String, ExtraFiles)> {
+    let input = match &args.input {
+        Some(path) => fs::read_to_string(path)
+            .with_context(|| format!("could not read input at `{}`", path.display()))?,
+        None => args.loader.load(entry.day)?,
+    };
+    Ok((input, entry.load_extra(&args.loader)?))
+}
+
+/// Run one day, printing the answers as we go.
+/// Returns whether everything worked.
+fn run_day(day: u32, args: &Args) -> bool {
+    // unwrap is ok because we checked when parsing the days
+    let entry = solution::get(day).unwrap();
+
+    let (input, extra) = match load_input(entry, args) {
+        Ok(it) => it,
+        Err(e) => {
+            eprintln!("Day {}: {:#}", day, e);
+            return false;
+        }
+    };
+
+    let start = Instant::now();
+    let parsed = entry.parse_with(&input, &extra);
+    let parse_time = start.elapsed();
+    let parsed = match parsed {
+        Ok(it) => it,
+        Err(e) => {
+            eprintln!("Day {}: parsing failed: {:#}", day, e);
+            return false;
+        }
+    };
+    println!("Day {}: parsed in {}", day, format_duration(parse_time));
+
+    // Answers are recorded for the input in the input directory,
+    // so they don't mean anything for some other input.
+    let answers = if args.input.is_some() {
+        None
+    } else {
+        match Answers::load(&args.loader, day) {
+            Ok(it) => it,
+            Err(e) => {
+                eprintln!("Day {}: {:#}", day, e);
+                return false;
+            }
+        }
+    };
+
+    let mut ok = true;
+    for &part in Part::ALL.iter() {
+        if args.part.is_some_and(|only| only != part) {
+            continue;
+        }
+
+        let start = Instant::now();
+        let answer = entry.solve_parsed(&parsed, part);
+        let time = format_duration(start.elapsed());
+        let answer = match answer {
+            Ok(it) => it,
+            Err(e) => {
+                eprintln!("  part {}: failed: {:#} ({})", part, e, time);
+                ok = false;
+                continue;
+            }
+        };
+        let check = match &answers {
+            Some(answers) => answers.check(part, &answer),
+            None => Check::Unknown,
+        };
+        match check {
+            Check::Correct => println!("  part {}: {} ({}) [correct]", part, answer, time),
+            Check::Unknown => println!("  part {}: {} ({})", part, answer, time),
+            Check::Regression { expected } => {
+                eprintln!(
+                    "  part {}: {} ({}) REGRESSION: the answer is {}",
+                    part, answer, time, expected
+                );
+                ok = false;
+            }
+            Check::KnownWrong { verdict, guess } => println!(
+                "  part {}: {} ({}) warning: known to be wrong ({} was {})",
+                part, answer, time, guess, verdict
+            ),
+        }
+    }
+    ok
+}
+
+/// Benchmark one day, printing the times and adding them to the report.
+/// Returns whether everything worked.
+fn bench_day(day: u32, args: &Args, baseline: Option<&Report>, report: &mut Report) -> bool {
+    // unwrap is ok because we checked when parsing the days
+    let entry = solution::get(day).unwrap();
+
+    let timings = load_input(entry, args)
+        .and_then(|(input, extra)| bench::bench_day(entry, &input, &extra, &args.bench_opts));
+    let timings = match timings {
+        Ok(it) => it,
+        Err(e) => {
+            eprintln!("Day {}: {:#}", day, e);
+            return false;
+        }
+    };
+
+    println!("Day {}:", day);
+    for timing in timings {
+        let comparison = baseline
+            .and_then(|baseline| baseline.get(day, timing.phase))
+            .map(|old| compare(old, &timing))
+            .unwrap_or_default();
+        println!(
+            "  {:<5}  median {:>9}  min {:>9}  mean {:>9}  ({} runs){}",
+            timing.phase,
+            format_duration(timing.median),
+            format_duration(timing.min),
+            format_duration(timing.mean),
+            timing.runs,
+            comparison
+        );
+        report.timings.push(timing);
+    }
+    true
+}
+
+/// Describe how the median time changed from an old timing
+fn compare(old: &Timing, new: &Timing) -> String {
+    let old_secs = old.median.as_secs_f64();
+    if old_secs == 0.0 {
+        return String::new();
+    }
+    let change = (new.median.as_secs_f64() - old_secs) / old_secs * 100.0;
+    format!("  was {} ({:+.1}%)", format_duration(old.median), change)
+}
+
+/// Benchmark all the days.
+/// Returns how many failed.
+fn bench_all(args: &Args) -> usize {
+    let baseline = match args.baseline.as_ref().map(Report::load).transpose() {
+        Ok(it) => it,
+        Err(e) => {
+            eprintln!("{:#}", e);
+            return args.days.len();
+        }
+    };
+
+    let mut report = Report::default();
+    let failures = args
+        .days
+        .iter()
+        .filter(|&&day| !bench_day(day, args, baseline.as_ref(), &mut report))
+        .count();
+
+    if let Some(path) = &args.save {
+        match report.save(path) {
+            Ok(()) => println!("Saved report to `{}`", path.display()),
+            Err(e) => {
+                eprintln!("{:#}", e);
+                return failures + 1;
+            }
+        }
+    }
+    failures
+}
+
+fn main() {
+    let args = match Args::parse(env::args().skip(1)) {
+        Ok(it) => it,
+        Err(e) => {
+            eprintln!("{:#}\n\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+
+    let start = Instant::now();
+    let failures = if args.bench {
+        bench_all(&args)
+    } else {
+        args.days
+            .iter()
+            .filter(|&&day| !run_day(day, &args))
+            .count()
+    };
+    println!("Total: {}", format_duration(start.elapsed()));
+
+    if failures > 0 {
+        eprintln!("{} day(s) failed", failures);
+        process::exit(1);
+    }
+}
+
+#[test]
+fn test_parse_days() -> Result<()> {
+    assert_eq!(parse_days("8")?, vec![8]);
+    assert_eq!(parse_days("3-5")?, vec![3, 4, 5]);
+    assert_eq!(parse_days("1,4-6,5,9")?, vec![1, 4, 5, 6, 9]);
+    assert_eq!(parse_days("all")?.len(), solution::REGISTRY.len());
+    assert!(parse_days("5-3").is_err());
+    assert!(parse_days("25").is_err());
+    assert!(parse_days("x").is_err());
+
+    Ok(())
+}
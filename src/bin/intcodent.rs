@@ -0,0 +1,177 @@
+//! Step through an intcodent program.
+//!
+//! ```text
+//! intcodent FILE [--break PC]... [--watch WATCH]... [--trace]
+//! intcodent FILE --disassemble
+//! intcodent FILE --analyze
+//! ```
+//!
+//! By default this reads debugger commands from stdin; type `help` to list them.
+//! With `--trace`, it runs the whole program instead, printing every instruction,
+//! and stops at the first breakpoint, watchpoint, or instruction that runs twice.
+//! With `--disassemble`, it prints the program without labels or comments and exits.
+//! With `--analyze`, it prints what it can work out without running the program:
+//! dead code, where it can't end from, and what the accumulator ends up as under each `jmp`/`nop` swap.
+
+use aoc_2020::intcodent::{
+    self, abstract_interp, analysis, Debugger, Executor, Instruction, Stop, Watchpoint,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+
+use std::{
+    env, fs,
+    io::{self, Write},
+    path::PathBuf,
+    process,
+};
+
+const USAGE: &str = "usage: intcodent FILE [--break PC]... [--watch WATCH]... [--trace]
+       intcodent FILE --disassemble
+       intcodent FILE --analyze
+
+WATCH is a register like `acc`, or a condition like `acc > 5`.
+Without --trace, debugger commands are read from stdin. Type `help` to list them.
+--disassemble prints the program with jumps as offsets and no labels or comments.
+--analyze prints dead code, where the program can't end from,
+and what acc could end up as under each jmp/nop swap.";
+
+struct Args {
+    file: PathBuf,
+    breakpoints: Vec<usize>,
+    watchpoints: Vec<Watchpoint>,
+    trace: bool,
+    disassemble: bool,
+    analyze: bool,
+}
+
+impl Args {
+    /// Parse the arguments (without the program name)
+    fn parse(args: impl Iterator<Item = String>) -> Result<Self> {
+        let mut file = None;
+        let mut breakpoints = Vec::new();
+        let mut watchpoints = Vec::new();
+        let mut trace = false;
+        let mut disassemble = false;
+        let mut analyze = false;
+
+        let mut args = args;
+        while let Some(arg) = args.next() {
+            let mut value = |flag: &str| {
+                args.next()
+                    .ok_or_else(|| anyhow!("`{}` needs a value", flag))
+            };
+            match arg.as_str() {
+                "--break" | "-b" => breakpoints.push(value(&arg)?.parse()?),
+                "--watch" | "-w" => watchpoints.push(value(&arg)?.parse()?),
+                "--trace" | "-t" => trace = true,
+                "--disassemble" | "-D" => disassemble = true,
+                "--analyze" | "-a" => analyze = true,
+                "--help" | "-h" => {
+                    println!("{}", USAGE);
+                    process::exit(0);
+                }
+                flag if flag.starts_with('-') => bail!("unknown flag `{}`", flag),
+                path => {
+                    if file.is_some() {
+                        bail!("only one file can be debugged at once");
+                    }
+                    file = Some(PathBuf::from(path));
+                }
+            }
+        }
+
+        Ok(Self {
+            file: file.ok_or_else(|| anyhow!("no file given"))?,
+            breakpoints,
+            watchpoints,
+            trace,
+            disassemble,
+            analyze,
+        })
+    }
+}
+
+fn run(args: Args) -> Result<()> {
+    let program = fs::read_to_string(&args.file)
+        .with_context(|| format!("could not read `{}`", args.file.display()))?;
+    let exe = Executor::new(&program)
+        .with_context(|| format!("in program at `{}`", args.file.display()))?;
+
+    if args.disassemble {
+        print!("{}", intcodent::disassemble(exe.program()));
+        return Ok(());
+    }
+    if args.analyze {
+        analyze(exe.program());
+        return Ok(());
+    }
+
+    let mut dbg = Debugger::new(exe);
+    for pc in args.breakpoints {
+        dbg.add_breakpoint(pc);
+    }
+    for watch in args.watchpoints {
+        dbg.add_watchpoint(watch);
+    }
+
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    if args.trace {
+        let stop = dbg.run();
+        for entry in dbg.executor().trace().unwrap_or_default() {
+            writeln!(stdout, "{}", entry)?;
+        }
+        writeln!(stdout, "{}", stop)?;
+        if stop != Stop::End {
+            writeln!(stdout, "acc = {}", dbg.executor().get_accumulator())?;
+        }
+    } else {
+        let stdin = io::stdin();
+        dbg.interactive(stdin.lock(), stdout)?;
+    }
+    Ok(())
+}
+
+/// Print everything the analyses can work out
+fn analyze(program: &[Instruction]) {
+    println!("{}", abstract_interp::interpret(program));
+
+    let bounds = abstract_interp::patch_bounds(program);
+    match bounds.final_acc() {
+        Some(acc) => println!("acc at the end with one swap: {}", acc),
+        None => println!("no single swap makes it end"),
+    }
+    for outcome in bounds.ending() {
+        // unwrap is ok because these are the ones that end
+        println!(
+            "  {} ends with {}",
+            outcome.patch,
+            outcome.final_acc.unwrap()
+        );
+    }
+
+    match analysis::patch_candidates(program) {
+        Ok(candidates) => {
+            println!("swaps:");
+            for candidate in candidates {
+                println!("  {}", candidate);
+            }
+        }
+        Err(e) => println!("couldn't check swaps: {:#}", e),
+    }
+}
+
+fn main() {
+    let args = match Args::parse(env::args().skip(1)) {
+        Ok(it) => it,
+        Err(e) => {
+            eprintln!("{:#}\n\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+    if let Err(e) = run(args) {
+        eprintln!("{:#}", e);
+        process::exit(1);
+    }
+}
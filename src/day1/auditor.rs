@@ -0,0 +1,227 @@
+use anyhow::{anyhow, Context, Result};
+
+use std::io::BufRead;
+
+use super::ksum::search;
+
+/// Some entries that sum to one of the targets.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Match {
+    pub target: i64,
+    /// Indices of the entries, in the order they were pushed
+    pub indices: Vec<usize>,
+    /// The entries themselves, in the same order
+    pub values: Vec<i64>,
+}
+
+impl Match {
+    /// Multiply the entries together, in i128.
+    /// That's always enough for two i64s, but three big ones can go past it,
+    /// so the product is checked and errors instead of overflowing.
+    pub fn product(&self) -> Result<i128> {
+        self.values.iter().try_fold(1i128, |acc, &val| {
+            acc.checked_mul(val as i128)
+                .ok_or_else(|| anyhow!("the product of {:?} overflowed", self.values))
+        })
+    }
+}
+
+/// Looks for sets of `k` entries that sum to any of several targets,
+/// taking entries one at a time so the whole report doesn't have to be around at once.
+///
+/// Each new entry is only checked against the ones before it,
+/// so every set gets found exactly once, when its last entry comes in.
+#[derive(Debug, Clone)]
+pub struct Auditor {
+    k: usize,
+    targets: Vec<i64>,
+    values: Vec<i64>,
+    /// (value, index) pairs, kept sorted
+    sorted: Vec<(i64, usize)>,
+    /// Everything found so far, for each target
+    matches: Vec<Vec<Vec<usize>>>,
+}
+
+impl Auditor {
+    /// Make an auditor looking for `k` entries summing to any of the `targets`.
+    pub fn new(k: usize, targets: &[i64]) -> Result<Self> {
+        if k == 0 {
+            return Err(anyhow!("can't look for sets of 0 entries"));
+        }
+        let mut targets = targets.to_vec();
+        targets.sort_unstable();
+        targets.dedup();
+        Ok(Self {
+            k,
+            matches: vec![Vec::new(); targets.len()],
+            targets,
+            values: Vec::new(),
+            sorted: Vec::new(),
+        })
+    }
+
+    /// Add an entry, returning any new matches it makes.
+    pub fn push(&mut self, value: i64) -> Vec<Match> {
+        let idx = self.values.len();
+        let mut found = Vec::new();
+        for (target_idx, &target) in self.targets.iter().enumerate() {
+            let mut sets = Vec::new();
+            search(
+                &self.sorted,
+                self.k - 1,
+                target as i128 - value as i128,
+                &mut Vec::new(),
+                &mut sets,
+            );
+            for mut set in sets {
+                set.sort_unstable();
+                let mut values = set.iter().map(|&idx| self.values[idx]).collect::<Vec<_>>();
+                set.push(idx);
+                values.push(value);
+                found.push(Match {
+                    target,
+                    indices: set.clone(),
+                    values,
+                });
+                self.matches[target_idx].push(set);
+            }
+        }
+
+        self.values.push(value);
+        // idx is bigger than everything already in there, so it goes after any equal values
+        let pos = self.sorted.partition_point(|&(val, _)| val <= value);
+        self.sorted.insert(pos, (value, idx));
+        found
+    }
+
+    /// Add a bunch of entries, returning all the new matches.
+    pub fn extend(&mut self, values: impl IntoIterator<Item = i64>) -> Vec<Match> {
+        values.into_iter().flat_map(|val| self.push(val)).collect()
+    }
+
+    /// Read entries one per line, skipping blank lines, and return all the new matches.
+    pub fn read(&mut self, reader: impl BufRead) -> Result<Vec<Match>> {
+        let mut found = Vec::new();
+        for (line_idx, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let value = line
+                .parse()
+                .with_context(|| format!("line {}: bad entry `{}`", line_idx + 1, line))?;
+            found.extend(self.push(value));
+        }
+        Ok(found)
+    }
+
+    /// Every entry so far
+    pub fn values(&self) -> &[i64] {
+        &self.values
+    }
+
+    /// The targets, sorted and without duplicates
+    pub fn targets(&self) -> &[i64] {
+        &self.targets
+    }
+
+    /// Every set found so far that sums to `target`, as indices.
+    /// Empty if it isn't one of the targets.
+    pub fn matches(&self, target: i64) -> &[Vec<usize>] {
+        match self.targets.binary_search(&target) {
+            Ok(idx) => &self.matches[idx],
+            Err(_) => &[],
+        }
+    }
+
+    /// Has any set summed to `target` yet?
+    pub fn has_match(&self, target: i64) -> bool {
+        !self.matches(target).is_empty()
+    }
+
+    /// Find the one set that sums to `target` and multiply it out.
+    /// Errors if there isn't one, or if there are several.
+    pub fn unique_product(&self, target: i64) -> Result<i128> {
+        let sets = self.matches(target);
+        match sets {
+            [set] => Match {
+                target,
+                indices: set.clone(),
+                values: set.iter().map(|&idx| self.values[idx]).collect(),
+            }
+            .product(),
+            [] => Err(anyhow!("no {} entries sum to {}", self.k, target)),
+            _ => Err(anyhow!(
+                "{} different sets of {} entries sum to {}",
+                sets.len(),
+                self.k,
+                target
+            )),
+        }
+    }
+}
+
+#[test]
+fn test_auditor() -> Result<()> {
+    let mut auditor = Auditor::new(2, &[2020, 10, 2020])?;
+    assert_eq!(auditor.targets(), &[10, 2020]);
+
+    assert!(auditor.extend(vec![1721, 979, 366]).is_empty());
+    let found = auditor.push(299);
+    assert_eq!(
+        found,
+        vec![Match {
+            target: 2020,
+            indices: vec![0, 3],
+            values: vec![1721, 299],
+        }]
+    );
+    assert!(auditor.has_match(2020));
+    assert!(!auditor.has_match(10));
+    assert!(!auditor.has_match(12345));
+    assert_eq!(auditor.unique_product(2020)?, 514579);
+    assert!(auditor.unique_product(10).is_err());
+
+    let found = auditor.read("\n5\n5\nnope\n".as_bytes());
+    assert!(found.is_err());
+    // the 5s got in before the bad line
+    assert_eq!(auditor.matches(10), &[vec![4, 5]]);
+
+    let mut auditor = Auditor::new(3, &[2020, 6])?;
+    let found = auditor.read("1721\n979\n366\n299\n675\n1456\n".as_bytes())?;
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].indices, vec![1, 2, 4]);
+    assert_eq!(auditor.unique_product(2020)?, 241861950);
+
+    // the same sets as searching everything at once
+    let values = [5, 5, 5, 1, 9, -4];
+    let mut auditor = Auditor::new(3, &[6, 15, 0])?;
+    auditor.extend(values.iter().copied());
+    for &target in auditor.targets() {
+        let mut sets = auditor.matches(target).to_vec();
+        sets.sort();
+        assert_eq!(sets, super::find_k_sum(&values, 3, target));
+    }
+
+    // this would overflow u32 and even i64
+    let mut auditor = Auditor::new(3, &[12_000_000_000])?;
+    auditor.extend(vec![4_000_000_000; 3]);
+    assert_eq!(
+        auditor.unique_product(12_000_000_000)?,
+        64_000_000_000_000_000_000_000_000_000
+    );
+    let mut auditor = Auditor::new(2, &[i64::MAX])?;
+    auditor.extend(vec![i64::MAX - 1, 1]);
+    assert_eq!(auditor.unique_product(i64::MAX)?, i64::MAX as i128 - 1);
+    let big = Match {
+        target: 0,
+        indices: vec![0, 1, 2, 3],
+        values: vec![i64::MAX; 4],
+    };
+    assert!(big.product().is_err());
+
+    assert!(Auditor::new(0, &[0]).is_err());
+
+    Ok(())
+}
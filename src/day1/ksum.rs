@@ -0,0 +1,212 @@
+use anyhow::{anyhow, bail, Result};
+
+/// Find every set of `k` different entries that sum to `target`.
+///
+/// Each set is returned as the indices of its entries, in increasing order,
+/// and the sets themselves are sorted. Equal values at different indices are different entries.
+///
+/// This sorts the values and then, for k >= 2, picks the first k - 2 entries
+/// and finds the last two with two pointers, so it's O(n^(k-1)) plus the size of the output.
+pub fn find_k_sum(values: &[i64], k: usize, target: i64) -> Vec<Vec<usize>> {
+    let mut sorted = values
+        .iter()
+        .copied()
+        .enumerate()
+        .map(|(idx, val)| (val, idx))
+        .collect::<Vec<_>>();
+    sorted.sort_unstable();
+
+    let mut out = Vec::new();
+    search(&sorted, k, target as i128, &mut Vec::new(), &mut out);
+    for set in out.iter_mut() {
+        set.sort_unstable();
+    }
+    out.sort_unstable();
+    out
+}
+
+/// Find `k` entries out of `sorted` (value, index pairs sorted by value) that sum to `target`,
+/// adding each set to `out` along with the indices already in `chosen`.
+pub(super) fn search(
+    sorted: &[(i64, usize)],
+    k: usize,
+    target: i128,
+    chosen: &mut Vec<usize>,
+    out: &mut Vec<Vec<usize>>,
+) {
+    if k > sorted.len() {
+        return;
+    }
+    match k {
+        0 => {
+            if target == 0 {
+                out.push(chosen.clone());
+            }
+        }
+        1 => {
+            let start = sorted.partition_point(|&(val, _)| (val as i128) < target);
+            for &(_, idx) in sorted[start..]
+                .iter()
+                .take_while(|&&(val, _)| val as i128 == target)
+            {
+                chosen.push(idx);
+                out.push(chosen.clone());
+                chosen.pop();
+            }
+        }
+        2 => two_pointer(sorted, target, chosen, out),
+        _ => {
+            let sum = |entries: &[(i64, usize)]| -> i128 {
+                entries.iter().map(|&(val, _)| val as i128).sum()
+            };
+            for first in 0..=sorted.len() - k {
+                let rest = &sorted[first + 1..];
+                let val = sorted[first].0 as i128;
+                // the smallest it could be is too big, and it only gets bigger from here
+                if val + sum(&rest[..k - 1]) > target {
+                    break;
+                }
+                // the biggest it could be is too small
+                if val + sum(&rest[rest.len() - (k - 1)..]) < target {
+                    continue;
+                }
+                chosen.push(sorted[first].1);
+                search(rest, k - 1, target - val, chosen, out);
+                chosen.pop();
+            }
+        }
+    }
+}
+
+/// Find every pair in `sorted` that sums to `target`
+fn two_pointer(sorted: &[(i64, usize)], target: i128, chosen: &[usize], out: &mut Vec<Vec<usize>>) {
+    let (mut lo, mut hi) = (0, sorted.len() - 1);
+    while lo < hi {
+        let (lo_val, hi_val) = (sorted[lo].0, sorted[hi].0);
+        let sum = lo_val as i128 + hi_val as i128;
+        if sum < target {
+            lo += 1;
+        } else if sum > target {
+            hi -= 1;
+        } else if lo_val == hi_val {
+            // everything in between is the same, so every pair of them works
+            for a in lo..hi {
+                for b in a + 1..=hi {
+                    out.push(with(chosen, &[sorted[a].1, sorted[b].1]));
+                }
+            }
+            return;
+        } else {
+            // pair up every copy of the low value with every copy of the high value
+            let lo_end = lo + sorted[lo..].iter().take_while(|e| e.0 == lo_val).count();
+            let hi_start = hi + 1
+                - sorted[..=hi]
+                    .iter()
+                    .rev()
+                    .take_while(|e| e.0 == hi_val)
+                    .count();
+            for a in lo..lo_end {
+                for b in hi_start..=hi {
+                    out.push(with(chosen, &[sorted[a].1, sorted[b].1]));
+                }
+            }
+            lo = lo_end;
+            hi = hi_start - 1;
+        }
+    }
+}
+
+fn with(chosen: &[usize], more: &[usize]) -> Vec<usize> {
+    chosen.iter().chain(more.iter()).copied().collect()
+}
+
+/// Find the one set of `k` entries that sums to `target`.
+/// Errors if there isn't one, or if there are several.
+pub fn unique_k_sum(values: &[i64], k: usize, target: i64) -> Result<Vec<usize>> {
+    let mut sets = find_k_sum(values, k, target);
+    match sets.len() {
+        0 => bail!("no {} entries sum to {}", k, target),
+        1 => Ok(sets.pop().unwrap()),
+        n => {
+            let shown = sets
+                .iter()
+                .take(5)
+                .map(|set| {
+                    let vals = set.iter().map(|&idx| values[idx].to_string());
+                    vals.collect::<Vec<_>>().join(" + ")
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            Err(anyhow!(
+                "{} different sets of {} entries sum to {}: {}{}",
+                n,
+                k,
+                target,
+                shown,
+                if n > 5 { ", ..." } else { "" }
+            ))
+        }
+    }
+}
+
+/// Multiply together the entries at these indices.
+/// Errors if it overflows.
+pub fn product(values: &[i64], indices: &[usize]) -> Result<i64> {
+    indices.iter().try_fold(1i64, |acc, &idx| {
+        acc.checked_mul(values[idx])
+            .ok_or_else(|| anyhow!("the product of the entries overflowed"))
+    })
+}
+
+#[test]
+fn test_find_k_sum() -> Result<()> {
+    let values = [1721, 979, 366, 299, 675, 1456];
+    assert_eq!(find_k_sum(&values, 2, 2020), vec![vec![0, 3]]);
+    assert_eq!(find_k_sum(&values, 3, 2020), vec![vec![1, 2, 4]]);
+    assert!(find_k_sum(&values, 4, 2020).is_empty());
+    assert_eq!(find_k_sum(&values, 1, 366), vec![vec![2]]);
+    assert_eq!(
+        find_k_sum(&values, 6, values.iter().sum()),
+        vec![vec![0, 1, 2, 3, 4, 5]]
+    );
+    assert!(find_k_sum(&values, 7, 0).is_empty());
+
+    // duplicates are different entries
+    let values = [5, 5, 5, 1, 9, -4];
+    assert_eq!(
+        find_k_sum(&values, 2, 10),
+        vec![vec![0, 1], vec![0, 2], vec![1, 2], vec![3, 4]]
+    );
+    assert_eq!(
+        find_k_sum(&values, 3, 6),
+        vec![vec![0, 1, 5], vec![0, 2, 5], vec![1, 2, 5], vec![3, 4, 5]]
+    );
+    // check against brute force
+    for k in 0..=4 {
+        for target in -10..30 {
+            let mut brute = Vec::new();
+            for mask in 0u32..1 << values.len() {
+                let set = (0..values.len())
+                    .filter(|&idx| mask & (1 << idx) != 0)
+                    .collect::<Vec<_>>();
+                if set.len() == k && set.iter().map(|&idx| values[idx]).sum::<i64>() == target {
+                    brute.push(set);
+                }
+            }
+            brute.sort();
+            assert_eq!(
+                find_k_sum(&values, k, target),
+                brute,
+                "k={} target={}",
+                k,
+                target
+            );
+        }
+    }
+
+    assert!(unique_k_sum(&values, 2, 10).is_err());
+    assert!(unique_k_sum(&values, 2, 100).is_err());
+    assert_eq!(unique_k_sum(&values, 2, 5)?, vec![4, 5]);
+
+    Ok(())
+}
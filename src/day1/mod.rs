@@ -1,34 +1,68 @@
-use once_cell::sync::Lazy;
+use anyhow::Result;
 
-static INPUT: Lazy<Vec<u32>> = Lazy::new(|| {
-    include_str!("input.txt")
-        .split('\n')
-        .filter_map(|it| it.parse().ok())
-        .collect()
-});
+use crate::solution::Solution;
 
-#[test]
-fn part1() {
-    for (idx1, &val1) in INPUT.iter().enumerate() {
-        for (idx2, &val2) in INPUT.iter().enumerate() {
-            if idx1 != idx2 && val1 + val2 == 2020 {
-                // we got it!
-                println!("{0} + {1} = 2020; {0} * {1} = {2}", val1, val2, val1 * val2);
-            }
-        }
+mod auditor;
+mod ksum;
+
+pub use auditor::{Auditor, Match};
+pub use ksum::{find_k_sum, product, unique_k_sum};
+
+/// What the entries have to sum to
+const TARGET: i64 = 2020;
+
+pub struct Day1;
+
+impl Solution for Day1 {
+    type Parsed = Vec<i64>;
+    type Answer1 = i64;
+    type Answer2 = i64;
+
+    fn parse(input: &str) -> Result<Self::Parsed> {
+        Ok(parse_expenses(input))
     }
+
+    fn part1(input: &Self::Parsed) -> Result<i64> {
+        let pair = unique_k_sum(input, 2, TARGET)?;
+        product(input, &pair)
+    }
+
+    fn part2(input: &Self::Parsed) -> Result<i64> {
+        let triple = unique_k_sum(input, 3, TARGET)?;
+        product(input, &triple)
+    }
+}
+
+/// Parse the expense report into a list of numbers.
+fn parse_expenses(input: &str) -> Vec<i64> {
+    input.lines().filter_map(|it| it.parse().ok()).collect()
 }
 
 #[test]
-fn part2() {
-    for (idx1, &val1) in INPUT.iter().enumerate() {
-        for (idx2, &val2) in INPUT.iter().enumerate() {
-            for (idx3, &val3) in INPUT.iter().enumerate() {
-                if idx1 != idx2 && idx2 != idx3 && idx1 != idx3 && val1 + val2 + val3 == 2020 {
-                    // we got it!
-                    println!("{} * {} * {} = {}", val1, val2, val3, val1 * val2 * val3);
-                }
-            }
-        }
-    }
+fn part1() -> Result<()> {
+    let input = Day1::parse(&test_input!(1))?;
+    let answer = Day1::part1(&input)?;
+    println!("product: {}", answer);
+    check_answer!(1, One, answer);
+
+    Ok(())
+}
+
+#[test]
+fn part2() -> Result<()> {
+    let input = Day1::parse(&test_input!(1))?;
+    let answer = Day1::part2(&input)?;
+    println!("product: {}", answer);
+    check_answer!(1, Two, answer);
+
+    Ok(())
+}
+
+#[test]
+fn part1_test() -> Result<()> {
+    let input = Day1::parse("1721\n979\n366\n299\n675\n1456")?;
+    assert_eq!(Day1::part1(&input)?, 514579);
+    assert_eq!(Day1::part2(&input)?, 241861950);
+
+    Ok(())
 }
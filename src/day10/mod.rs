@@ -2,7 +2,31 @@ use anyhow::{bail, Result};
 
 use std::collections::{HashMap, HashSet};
 
-const INPUT: &str = include_str!("input.txt");
+use crate::solution::Solution;
+
+pub struct Day10;
+
+impl Solution for Day10 {
+    type Parsed = HashSet<u32>;
+    type Answer1 = u32;
+    type Answer2 = u128;
+
+    fn parse(input: &str) -> Result<Self::Parsed> {
+        input.lines().map(|line| Ok(line.parse::<u32>()?)).collect()
+    }
+
+    fn part1(adapters: &Self::Parsed) -> Result<u32> {
+        let out = count_joltage_jumps(adapters.clone())?;
+        Ok(out[0] * out[2])
+    }
+
+    fn part2(adapters: &Self::Parsed) -> Result<u128> {
+        if adapters.is_empty() {
+            bail!("there were no adapters");
+        }
+        Ok(count_joltage_perms(adapters.clone()))
+    }
+}
 
 /// Given a set of adapters, find the number of jumps of each voltage
 fn count_joltage_jumps(mut input: HashSet<u32>) -> Result<[u32; 3]> {
@@ -72,25 +96,20 @@ fn count_joltage_perms(input: HashSet<u32>) -> u128 {
 
 #[test]
 fn part1() -> Result<()> {
-    let input = INPUT
-        .lines()
-        .map(|line| Ok(line.parse::<u32>()?))
-        .collect::<Result<_>>()?;
-    let out = count_joltage_jumps(input)?;
-    let solution = out[0] * out[2];
-    println!("solution: {}", solution);
+    let adapters = Day10::parse(&test_input!(10))?;
+    let answer = Day10::part1(&adapters)?;
+    println!("solution: {}", answer);
+    check_answer!(10, One, answer);
 
     Ok(())
 }
 
 #[test]
 fn part2() -> Result<()> {
-    let input = INPUT
-        .lines()
-        .map(|line| Ok(line.parse::<u32>()?))
-        .collect::<Result<_>>()?;
-    let solution = count_joltage_perms(input);
-    println!("solution: {}", solution);
+    let adapters = Day10::parse(&test_input!(10))?;
+    let answer = Day10::part2(&adapters)?;
+    println!("solution: {}", answer);
+    check_answer!(10, Two, answer);
 
     Ok(())
 }
@@ -1,14 +1,52 @@
-use std::{
-    convert::TryInto,
-    io::{stdout, Write},
-    iter::{self, repeat},
-};
+use std::io::{stdout, Write};
 
-use anyhow::{bail, Result};
+use anyhow::Result;
 use fwdansi::write_ansi;
 use termcolor::{Color, ColorChoice, ColorSpec, WriteColor};
 
-const INPUT: &str = include_str!("input.txt");
+use crate::{
+    grid::{Grid, Tile},
+    solution::Solution,
+};
+
+pub struct Day11;
+
+impl Solution for Day11 {
+    type Parsed = Ferry;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Ferry> {
+        Ferry::new(input)
+    }
+
+    fn part1(ferry: &Ferry) -> Result<usize> {
+        let mut ferry = ferry.clone();
+
+        // what is this, c?
+        while ferry.update_part1() {
+            // ferry.print()?;
+            // println!();
+        }
+
+        Ok(ferry.occupied())
+    }
+
+    fn part2(ferry: &Ferry) -> Result<usize> {
+        let mut ferry = ferry.clone();
+
+        // what is this, c?
+        // let mut idx = 0;
+        while ferry.update_part2() {
+            // idx += 1;
+            // println!("step {}:", idx); // cover yourself in oil
+            // ferry.print()?;
+            // println!();
+        }
+
+        Ok(ferry.occupied())
+    }
+}
 
 const SMOL_INPUT: &str = r"L.LL.LL.LL
 LLLLLLL.LL
@@ -22,163 +60,110 @@ L.LLLLLL.L
 L.LLLLL.LL";
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
-enum Spot {
+pub enum Spot {
     Floor,
     EmptyChair,
     FullChair,
 }
 
-struct Ferry {
-    width: usize,
-    height: usize,
+impl Tile for Spot {
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            '.' => Some(Spot::Floor),
+            'L' => Some(Spot::EmptyChair),
+            '#' => Some(Spot::FullChair),
+            _ => None,
+        }
+    }
+
+    fn to_char(&self) -> char {
+        match self {
+            Spot::Floor => '.',
+            Spot::EmptyChair => 'L',
+            Spot::FullChair => '#',
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Ferry {
     /// Spots that passengers sit in.
-    /// `y * width + x`
-    spots: Vec<Spot>,
+    spots: Grid<Spot>,
 }
 
 impl Ferry {
     /// Make a new Ferry from the input
     fn new(i: &str) -> Result<Ferry> {
-        let mut width = None;
-        let lines = i.lines();
-        let spots = lines
-            .flat_map(|line| {
-                width.get_or_insert_with(|| line.chars().count());
-                line.chars().map(|c| {
-                    Ok(match c {
-                        '.' => Spot::Floor,
-                        'L' => Spot::EmptyChair,
-                        '#' => Spot::FullChair,
-                        oh_no => bail!("unknown character `{}`", oh_no),
-                    })
-                })
-            })
-            .collect::<Result<Vec<_>>>()?;
-        let height = i.lines().count();
-
         Ok(Ferry {
-            width: width.unwrap(),
-            height,
-            spots,
+            spots: Grid::parse(i)?,
         })
     }
 
-    /// Update one step using part 1's rules. Return if there were any changes.
-    fn update_part1(&mut self) -> bool {
-        let mut spots = self.spots.clone();
+    /// Update one step, counting the full seats around each seat with `filled_seats`.
+    /// People leave if there are at least `tolerance` full seats around them.
+    /// Return if there were any changes.
+    fn update(
+        &mut self,
+        filled_seats: impl Fn(&Grid<Spot>, usize, usize) -> usize,
+        tolerance: usize,
+    ) -> bool {
         let mut any_change = false;
 
-        for x in 0..self.width {
-            'y: for y in 0..self.height {
-                let seat = spots.get_mut(y * self.width + x).unwrap();
-                if *seat == Spot::Floor {
-                    continue 'y;
-                }
-                let filled_seats = [
-                    (-1, -1),
-                    (0, -1),
-                    (1, -1),
-                    (1, 0),
-                    (1, 1),
-                    (0, 1),
-                    (-1, 1),
-                    (-1, 0),
-                ]
-                .iter()
-                .filter_map(|&(dx, dy)| {
-                    let check_x: usize = (x as i32 + dx).try_into().ok()?;
-                    let check_y: usize = (y as i32 + dy).try_into().ok()?;
-                    // no out of bounds >:(
-                    if check_x >= self.width || check_y >= self.height {
-                        return None;
-                    }
-                    self.spots
-                        .get(check_y * self.width + check_x)
-                        .and_then(|&spot| {
-                            if spot == Spot::FullChair {
-                                Some(())
-                            } else {
-                                None
-                            }
-                        })
-                })
-                .count();
-                if *seat == Spot::EmptyChair && filled_seats == 0 {
-                    any_change = true;
-                    *seat = Spot::FullChair;
-                } else if *seat == Spot::FullChair && filled_seats >= 4 {
-                    any_change = true;
-                    *seat = Spot::EmptyChair;
-                }
+        let spots = self.spots.map(|(x, y), &seat| {
+            if seat == Spot::Floor {
+                return seat;
             }
-        }
+            let filled_seats = filled_seats(&self.spots, x, y);
+            if seat == Spot::EmptyChair && filled_seats == 0 {
+                any_change = true;
+                Spot::FullChair
+            } else if seat == Spot::FullChair && filled_seats >= tolerance {
+                any_change = true;
+                Spot::EmptyChair
+            } else {
+                seat
+            }
+        });
 
         self.spots = spots;
         any_change
     }
 
+    /// Update one step using part 1's rules. Return if there were any changes.
+    fn update_part1(&mut self) -> bool {
+        self.update(
+            |spots, x, y| {
+                spots
+                    .neighbors8(x, y)
+                    .filter(|&pos| spots[pos] == Spot::FullChair)
+                    .count()
+            },
+            4,
+        )
+    }
+
     /// Update one step using part 2's rules.
     /// Return if there were any changes
     fn update_part2(&mut self) -> bool {
-        let mut spots = self.spots.clone();
-        let mut any_change = false;
-
-        for x in 0..self.width {
-            'y: for y in 0..self.height {
-                let seat = spots.get_mut(y * self.width + x).unwrap();
-                if *seat == Spot::Floor {
-                    continue 'y;
-                }
-                let filled_seats = [
-                    (-1, -1),
-                    (0, -1),
-                    (1, -1),
-                    (1, 0),
-                    (1, 1),
-                    (0, 1),
-                    (-1, 1),
-                    (-1, 0),
-                ]
-                .iter()
-                .filter(|&(dx, dy)| {
-                    // Check if anything is down that way
-                    // there's gotta be a better way to do this
-                    let x_iter: Box<dyn Iterator<Item = usize>> = match dx {
-                        -1 => Box::new((0..x).rev()),
-                        0 => Box::new(iter::repeat(x)),
-                        1 => Box::new((x + 1)..self.width),
-                        _ => unreachable!(),
-                    };
-                    let y_iter: Box<dyn Iterator<Item = usize>> = match dy {
-                        -1 => Box::new((0..y).rev()),
-                        0 => Box::new(iter::repeat(y)),
-                        1 => Box::new((y + 1)..self.height),
-                        _ => unreachable!(),
-                    };
-                    // find the first not-floor spot
-                    // and check if it's a full chair
-                    x_iter.zip(y_iter).find_map(|(check_x, check_y)| {
-                        let spot_there = *self.spots.get(check_y * self.width + check_x).unwrap();
-                        match spot_there {
-                            Spot::Floor => None,
-                            Spot::EmptyChair => Some(false),
-                            Spot::FullChair => Some(true),
-                        }
-                    }) == Some(true)
-                })
-                .count();
-                if *seat == Spot::EmptyChair && filled_seats == 0 {
-                    any_change = true;
-                    *seat = Spot::FullChair;
-                } else if *seat == Spot::FullChair && filled_seats >= 5 {
-                    any_change = true;
-                    *seat = Spot::EmptyChair;
-                }
-            }
-        }
+        self.update(
+            |spots, x, y| {
+                // find the first not-floor spot in each direction
+                // and check if it's a full chair
+                spots
+                    .visible(x, y, |&spot| spot != Spot::Floor)
+                    .filter(|&pos| spots[pos] == Spot::FullChair)
+                    .count()
+            },
+            5,
+        )
+    }
 
-        self.spots = spots;
-        any_change
+    /// Count how many seats are full
+    fn occupied(&self) -> usize {
+        self.spots
+            .iter()
+            .filter(|(_, &spot)| spot == Spot::FullChair)
+            .count()
     }
 
     /// Print this to the console
@@ -187,9 +172,9 @@ impl Ferry {
         // write_ansi(&mut stdout, b"\x1b[2J\x1b[H")?;
 
         let mut col = ColorSpec::new();
-        for y in 0..self.height {
-            for x in 0..self.width {
-                let (ch, _) = match self.spots[y * self.width + x] {
+        for row in self.spots.rows() {
+            for spot in row {
+                let (ch, _) = match spot {
                     Spot::Floor => (b".", col.set_fg(Some(Color::Green))),
                     Spot::EmptyChair => (b"L", col.set_fg(Some(Color::Blue))),
                     Spot::FullChair => (b"#", col.set_fg(Some(Color::Cyan))),
@@ -206,45 +191,29 @@ impl Ferry {
 
 #[test]
 fn part1() -> Result<()> {
-    let mut ferry = Ferry::new(INPUT)?;
-
-    // what is this, c?
-    while ferry.update_part1() {
-        // ferry.print()?;
-        // println!();
-    }
-
-    let occupied = ferry
-        .spots
-        .iter()
-        .filter(|spot| **spot == Spot::FullChair)
-        .count();
-    println!("seats occupied: {}", occupied);
+    let ferry = Day11::parse(&test_input!(11))?;
+    let answer = Day11::part1(&ferry)?;
+    println!("seats occupied: {}", answer);
+    check_answer!(11, One, answer);
 
     Ok(())
 }
 
 #[test]
 fn part2() -> Result<()> {
-    let mut ferry = Ferry::new(INPUT)?;
-
-    // what is this, c?
-    // let mut idx = 0;
-    while ferry.update_part2() {
-        // idx += 1;
-        // println!("step {}:", idx); // cover yourself in oil
-        // ferry.print()?;
-        // println!();
-    }
-
-    let occupied = ferry
-        .spots
-        .iter()
-        .filter(|spot| **spot == Spot::FullChair)
-        .count();
-    println!("seats occupied: {}", occupied);
+    let ferry = Day11::parse(&test_input!(11))?;
+    let answer = Day11::part2(&ferry)?;
+    println!("seats occupied: {}", answer);
+    check_answer!(11, Two, answer);
 
     Ok(())
+}
 
-    // not 4
+#[test]
+fn smol_test() -> Result<()> {
+    let ferry = Day11::parse(SMOL_INPUT)?;
+    assert_eq!(Day11::part1(&ferry)?, 37);
+    assert_eq!(Day11::part2(&ferry)?, 26);
+
+    Ok(())
 }
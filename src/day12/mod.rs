@@ -1,7 +1,36 @@
 use anyhow::{bail, Result};
 use cogs_gamedev::{directions::Direction4, int_coords::ICoord};
 
-const INPUT: &str = include_str!("input.txt");
+use crate::solution::Solution;
+
+pub struct Day12;
+
+impl Solution for Day12 {
+    /// Each action, one per line
+    type Parsed = Vec<String>;
+    type Answer1 = isize;
+    type Answer2 = isize;
+
+    fn parse(input: &str) -> Result<Self::Parsed> {
+        Ok(input.lines().map(String::from).collect())
+    }
+
+    fn part1(actions: &Self::Parsed) -> Result<isize> {
+        let mut ferry = Ferry::new();
+        for line in actions {
+            ferry.act_on(line)?;
+        }
+        Ok(ferry.distance())
+    }
+
+    fn part2(actions: &Self::Parsed) -> Result<isize> {
+        let mut ferry = FerryWithWaypoint::new();
+        for line in actions {
+            ferry.act_on(line)?;
+        }
+        Ok(ferry.distance())
+    }
+}
 
 struct Ferry {
     position: ICoord,
@@ -99,27 +128,20 @@ impl FerryWithWaypoint {
 
 #[test]
 fn part1() -> Result<()> {
-    let mut ferry = Ferry::new();
-    for line in INPUT.lines() {
-        ferry.act_on(line)?;
-    }
-    println!("distance gone: {}", ferry.distance());
+    let actions = Day12::parse(&test_input!(12))?;
+    let answer = Day12::part1(&actions)?;
+    println!("distance gone: {}", answer);
+    check_answer!(12, One, answer);
 
     Ok(())
 }
 
 #[test]
 fn part2() -> Result<()> {
-    let mut ferry = FerryWithWaypoint::new();
-    for line in INPUT.lines() {
-        ferry.act_on(line)?;
-    }
-    println!("distance gone: {}", ferry.distance());
+    let actions = Day12::parse(&test_input!(12))?;
+    let answer = Day12::part2(&actions)?;
+    println!("distance gone: {}", answer);
+    check_answer!(12, Two, answer);
 
     Ok(())
-
-    // 129063 is too high
-    // 11711 is too low
-    // not 20212
-    // not 18038
 }
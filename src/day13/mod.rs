@@ -1,8 +1,33 @@
 use anyhow::{anyhow, bail, Result};
 
-const INPUT: &str = include_str!("input.txt");
+use crate::solution::Solution;
 
-struct Timetable {
+pub struct Day13;
+
+impl Solution for Day13 {
+    type Parsed = Timetable;
+    type Answer1 = u32;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Timetable> {
+        Timetable::new(input)
+    }
+
+    fn part1(timetable: &Timetable) -> Result<u32> {
+        if timetable.buses.iter().all(Option::is_none) {
+            bail!("there are no buses");
+        }
+        let (id, dt) = timetable.leave();
+        Ok(id * dt)
+    }
+
+    fn part2(timetable: &Timetable) -> Result<u64> {
+        // they told us where we're starting
+        Ok(timetable.earliest_sequence(100000000000000))
+    }
+}
+
+pub struct Timetable {
     /// When I need to leave
     depart: u32,
     /// IDs of the buses, or None if it's X
@@ -35,13 +60,11 @@ impl Timetable {
     /// Find the `(id, dtime)` of when you can leave
     fn leave(&self) -> (u32, u32) {
         for time in self.depart.. {
-            for bus in self.buses.iter() {
-                if let Some(bus) = bus {
-                    if time % bus == 0 {
-                        // poggers
-                        let dt = time - self.depart;
-                        return (*bus, dt);
-                    }
+            for bus in self.buses.iter().flatten() {
+                if time.is_multiple_of(*bus) {
+                    // poggers
+                    let dt = time - self.depart;
+                    return (*bus, dt);
                 }
             }
         }
@@ -62,7 +85,7 @@ impl Timetable {
                 .enumerate()
                 .all(|(idx, bus)| {
                     if let Some(bus) = bus {
-                        (time + idx as u64) % *bus as u64 == 0
+                        (time + idx as u64).is_multiple_of(*bus as u64)
                     } else {
                         // if there's no requirement there, it's always OK
                         true
@@ -102,21 +125,20 @@ impl Timetable {
 
 #[test]
 fn part1() -> Result<()> {
-    let timetable = Timetable::new(INPUT)?;
-    let (id, dt) = timetable.leave();
-    println!("bus #{} with {}m waiting = {}", id, dt, id * dt);
+    let timetable = Day13::parse(&test_input!(13))?;
+    let answer = Day13::part1(&timetable)?;
+    println!("bus id * waiting time = {}", answer);
+    check_answer!(13, One, answer);
     Ok(())
 }
 
 #[test]
 fn part2() -> Result<()> {
-    let timetable = Timetable::new(INPUT)?;
-    // they told us where we're starting
-    let time = timetable.earliest_sequence(100000000000000);
-    println!("earliest time: {}", time);
+    let timetable = Day13::parse(&test_input!(13))?;
+    let answer = Day13::part2(&timetable)?;
+    println!("earliest time: {}", answer);
+    check_answer!(13, Two, answer);
     Ok(())
-
-    // 1602195830254321 is too high
 }
 
 #[test]
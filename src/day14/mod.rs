@@ -4,7 +4,36 @@ use regex::Regex;
 
 use std::{collections::BTreeMap, convert::identity};
 
-const INPUT: &str = include_str!("input.txt");
+use crate::solution::Solution;
+
+pub struct Day14;
+
+impl Solution for Day14 {
+    /// Each instruction, one per line
+    type Parsed = Vec<String>;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Parsed> {
+        Ok(input.lines().map(String::from).collect())
+    }
+
+    fn part1(lines: &Self::Parsed) -> Result<u64> {
+        let mut program = ProgramP1::new();
+        for line in lines {
+            program.input(line)?;
+        }
+        Ok(program.memory.values().sum())
+    }
+
+    fn part2(lines: &Self::Parsed) -> Result<u64> {
+        let mut program = ProgramP2::new();
+        for line in lines {
+            program.input(line)?;
+        }
+        Ok(program.memory.values().sum())
+    }
+}
 
 /// Program for part 1
 struct ProgramP1 {
@@ -185,32 +214,22 @@ impl ProgramP2 {
 
 #[test]
 fn part1() -> Result<()> {
-    let mut program = ProgramP1::new();
-    for line in INPUT.lines() {
-        program.input(line)?;
-    }
-    // Get sum
-    let sum: u64 = program.memory.values().sum();
-    println!("sum: {}", sum);
+    let program = Day14::parse(&test_input!(14))?;
+    let answer = Day14::part1(&program)?;
+    println!("sum: {}", answer);
+    check_answer!(14, One, answer);
 
     Ok(())
-
-    // 13484360637149: too high
 }
 
 #[test]
 fn part2() -> Result<()> {
-    let mut program = ProgramP2::new();
-    for line in INPUT.lines() {
-        program.input(line)?;
-    }
-    // Get sum
-    let sum: u64 = program.memory.values().sum();
-    println!("sum: {}", sum);
+    let program = Day14::parse(&test_input!(14))?;
+    let answer = Day14::part2(&program)?;
+    println!("sum: {}", answer);
+    check_answer!(14, Two, answer);
 
     Ok(())
-
-    // 1804269557402: too low
 }
 
 #[test]
@@ -219,13 +238,9 @@ fn part1_test() -> Result<()> {
 mem[8] = 11
 mem[7] = 101
 mem[8] = 0";
-    let mut program = ProgramP1::new();
-    for line in input.lines() {
-        program.input(line)?;
-    }
-    // Get sum
-    let sum: u64 = program.memory.values().sum();
+    let sum = Day14::part1(&Day14::parse(input)?)?;
     println!("sum: {}", sum);
+    assert_eq!(sum, 165);
 
     Ok(())
 }
@@ -236,13 +251,9 @@ fn part2_test() -> Result<()> {
 mem[42] = 100
 mask = 00000000000000000000000000000000X0XX
 mem[26] = 1";
-    let mut program = ProgramP2::new();
-    for line in input.lines() {
-        program.input(line)?;
-    }
-    // Get sum
-    let sum: u64 = program.memory.values().sum();
+    let sum = Day14::part2(&Day14::parse(input)?)?;
     println!("sum: {}", sum);
+    assert_eq!(sum, 208);
 
     Ok(())
 }
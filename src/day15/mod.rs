@@ -1,7 +1,43 @@
+use anyhow::{anyhow, Result};
+
 use std::collections::HashMap;
 
+use crate::solution::Solution;
+
 const INPUT: &str = "13,0,10,12,1,5,8";
 
+pub struct Day15;
+
+impl Solution for Day15 {
+    /// The starting numbers
+    type Parsed = Vec<usize>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Parsed> {
+        input
+            .trim()
+            .split(',')
+            .map(|num| Ok(num.trim().parse()?))
+            .collect()
+    }
+
+    fn part1(input: &Self::Parsed) -> Result<usize> {
+        spoken_at(input, 2020)
+    }
+
+    fn part2(input: &Self::Parsed) -> Result<usize> {
+        spoken_at(input, 30000000)
+    }
+}
+
+/// Get the `turn`th number spoken (counting from 1)
+fn spoken_at(input: &[usize], turn: usize) -> Result<usize> {
+    GameIter::new(input)
+        .nth(turn - 1)
+        .ok_or_else(|| anyhow!("the game ended before turn {}", turn))
+}
+
 #[derive(Debug)]
 struct GameIter<'s> {
     /// The input numbers
@@ -60,25 +96,23 @@ impl<'s> Iterator for GameIter<'s> {
 }
 
 #[test]
-fn part1() {
-    let input = INPUT
-        .split(',')
-        .map(|num| num.parse().unwrap())
-        .collect::<Vec<_>>();
-
-    println!("2020th: {:?}", GameIter::new(&input).nth(2020 - 1));
+fn part1() -> Result<()> {
+    let input = Day15::parse(INPUT)?;
+    let answer = Day15::part1(&input)?;
+    println!("2020th: {}", answer);
+    check_answer!(15, One, answer);
 
-    // not 1
+    Ok(())
 }
 
 #[test]
-fn part2() {
-    let input = INPUT
-        .split(',')
-        .map(|num| num.parse().unwrap())
-        .collect::<Vec<_>>();
+fn part2() -> Result<()> {
+    let input = Day15::parse(INPUT)?;
+    let answer = Day15::part2(&input)?;
+    println!("30000000th: {}", answer);
+    check_answer!(15, Two, answer);
 
-    println!("30000000th: {:?}", GameIter::new(&input).nth(30000000 - 1));
+    Ok(())
 }
 
 #[test]
@@ -7,7 +7,44 @@ use anyhow::{bail, Result};
 use parsing::Input;
 use requirements::Requirement;
 
-const INPUT: &str = include_str!("input.txt");
+use crate::solution::Solution;
+
+pub struct Day16;
+
+impl Solution for Day16 {
+    type Parsed = Input;
+    type Answer1 = u32;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Input> {
+        Input::new(input)
+    }
+
+    fn part1(input: &Input) -> Result<u32> {
+        Ok(input
+            .other_tickets
+            .iter()
+            .filter_map(|ticket| error_rate(ticket, &input.requirements))
+            .sum())
+    }
+
+    fn part2(input: &Input) -> Result<u64> {
+        let mapping = identify_fields(input)?;
+        let answer: u64 = mapping
+            .iter()
+            .filter_map(|(field_name, field_idx)| {
+                if !field_name.starts_with("departure") {
+                    // we don't care
+                    return None;
+                }
+                // get my field
+                let myfield = *input.my_ticket.get(*field_idx).unwrap();
+                Some(myfield as u64)
+            })
+            .product();
+        Ok(answer)
+    }
+}
 
 /// See if a ticket is valid given the requirements.
 /// Return None if it's ok, or Some with the error rate if it's broken.
@@ -24,22 +61,9 @@ fn error_rate(ticket: &[u32], reqs: &HashMap<String, Requirement>) -> Option<u32
     }
 }
 
-#[test]
-fn part1() -> Result<()> {
-    let input = Input::new(INPUT)?;
-    let error_rate: u32 = input
-        .other_tickets
-        .iter()
-        .filter_map(|ticket| error_rate(ticket, &input.requirements))
-        .sum();
-    println!("total error rate: {}", error_rate);
-
-    Ok(())
-}
-
-#[test]
-fn part2() -> Result<()> {
-    let input = Input::new(INPUT)?;
+/// Figure out which field is at which index on the tickets.
+/// Returns a map of field names to indices.
+fn identify_fields(input: &Input) -> Result<HashMap<String, usize>> {
     let valid_tickets = input
         .other_tickets
         .iter()
@@ -134,29 +158,34 @@ fn part2() -> Result<()> {
         })
         .collect::<Result<Vec<_>>>()?;
     // invert it
-    let mapping = mapping
+    Ok(mapping
         .into_iter()
         .enumerate()
-        .map(|(idx, field)| {
-            println!("- #{} -> {}", idx + 1, field);
-            (field, idx)
-        })
-        .collect::<HashMap<_, _>>();
+        .map(|(idx, field)| (field, idx))
+        .collect::<HashMap<_, _>>())
+}
 
-    let answer: u64 = mapping
-        .iter()
-        .filter_map(|(field_name, field_idx)| {
-            if !field_name.starts_with("departure") {
-                // we don't care
-                return None;
-            }
-            // get my field
-            let myfield = *input.my_ticket.get(*field_idx).unwrap();
-            Some(myfield as u64)
-        })
-        .product();
+#[test]
+fn part1() -> Result<()> {
+    let input = Day16::parse(&test_input!(16))?;
+    let answer = Day16::part1(&input)?;
+    println!("total error rate: {}", answer);
+    check_answer!(16, One, answer);
+
+    Ok(())
+}
+
+#[test]
+fn part2() -> Result<()> {
+    let input = Day16::parse(&test_input!(16))?;
+    let mut mapping = identify_fields(&input)?.into_iter().collect::<Vec<_>>();
+    mapping.sort_by_key(|(_, idx)| *idx);
+    for (field, idx) in mapping {
+        println!("- #{} -> {}", idx + 1, field);
+    }
+    let answer = Day16::part2(&input)?;
     println!("answer: {}", answer);
+    check_answer!(16, Two, answer);
 
     Ok(())
 }
- 
\ No newline at end of file
@@ -1,6 +1,8 @@
 use std::collections::HashMap;
 
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
+
+use crate::records::{self, Group};
 
 use super::requirements::Requirement;
 
@@ -19,7 +21,7 @@ impl Input {
     /// Parse the input string into an Input
     pub fn new(i: &str) -> Result<Self> {
         // Split this into sections
-        let sections = i.split("\n\n").collect::<Vec<_>>();
+        let sections = records::groups(i);
         let (reqs, my_ticket, their_tickets) = match sections.as_slice() {
             [reqs, my_ticket, their_tickets] => (reqs, my_ticket, their_tickets),
             _ => bail!("could not split into sections"),
@@ -27,14 +29,14 @@ impl Input {
 
         let reqs = parse_requirements(reqs)?;
 
-        let my_tickets = parse_tickets(my_ticket)?;
+        let my_tickets = parse_tickets(my_ticket, "your ticket:")?;
         if my_tickets.len() != 1 {
             bail!("wrong number of my tickets, got {}", my_tickets.len())
         }
         // not sure why i have to do this clone ... can't i just consume the whole my_tickets?
         let my_ticket = my_tickets[0].clone();
 
-        let other_tickets = parse_tickets(their_tickets)?;
+        let other_tickets = parse_tickets(their_tickets, "nearby tickets:")?;
 
         Ok(Self {
             requirements: reqs,
@@ -44,13 +46,14 @@ impl Input {
     }
 }
 
-fn parse_requirements(i: &str) -> Result<HashMap<String, Requirement>> {
-    i.lines()
+fn parse_requirements(i: &Group) -> Result<HashMap<String, Requirement>> {
+    i.numbered()
+        .iter()
         .map(|line| {
-            let split = line.split(": ").collect::<Vec<_>>();
+            let split = line.text.split(": ").collect::<Vec<_>>();
             let (key, reqs) = match split.as_slice() {
                 [key, reqs] => (key, reqs),
-                _ => bail!("could not split requirement"),
+                _ => bail!("could not split requirement on line {}", line.number),
             };
             let reqs_split = reqs.split_ascii_whitespace().collect::<Vec<_>>();
             let (req, reqs_rem) = Requirement::new(&reqs_split)?;
@@ -65,11 +68,46 @@ fn parse_requirements(i: &str) -> Result<HashMap<String, Requirement>> {
         .collect()
 }
 
-fn parse_tickets(i: &str) -> Result<Vec<Vec<u32>>> {
-    // skip line #1 where it has the header
-    // like `your ticket`
-    i.lines()
-        .skip(1)
-        .map(|line| line.split(',').map(|num| Ok(num.parse()?)).collect())
+fn parse_tickets(i: &Group, header: &str) -> Result<Vec<Vec<u32>>> {
+    // line #1 has the header
+    // like `your ticket:`
+    // (groups are never empty so the unwrap is ok)
+    let (first, lines) = i.numbered().split_first().unwrap();
+    if first.text != header {
+        bail!(
+            "expected `{}` on line {} but found `{}`",
+            header,
+            first.number,
+            first.text
+        );
+    }
+    lines
+        .iter()
+        .map(|line| {
+            line.text
+                .split(',')
+                .map(|num| {
+                    num.trim()
+                        .parse()
+                        .with_context(|| format!("bad number `{}` on line {}", num, line.number))
+                })
+                .collect()
+        })
         .collect()
 }
+
+#[test]
+fn test_parse() -> Result<()> {
+    let input = Input::new(
+        "class: 1-3 or 5-7\r\nrow: 6-11 or 33-44\r\n\r\nyour ticket:\r\n7,1,14\r\n\r\nnearby tickets:\r\n7,3,47\r\n40,4,50\r\n",
+    )?;
+    assert_eq!(input.requirements.len(), 2);
+    assert_eq!(input.my_ticket, vec![7, 1, 14]);
+    assert_eq!(input.other_tickets, vec![vec![7, 3, 47], vec![40, 4, 50]]);
+
+    let err =
+        Input::new("class: 1-3 or 5-7\n\nyour ticket:\n7,x\n\nnearby tickets:\n1,2").unwrap_err();
+    assert_eq!(err.to_string(), "bad number `x` on line 4");
+
+    Ok(())
+}
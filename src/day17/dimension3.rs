@@ -1,7 +1,10 @@
-use anyhow::{bail, Result};
+use anyhow::Result;
 
 use std::collections::{HashMap, HashSet};
 
+use crate::grid::Grid;
+
+#[derive(Clone)]
 pub struct Dimension {
     /// A set of all the active cubes
     world: HashSet<Coord3>,
@@ -14,19 +17,11 @@ impl Dimension {
     /// The upper-left of the input is at (0, 0, 0).
     /// X increases to the right, Y down.
     pub fn new(i: &str) -> Result<Self> {
-        let mut world = HashSet::new();
-        for (y, line) in i.lines().enumerate() {
-            for (x, chr) in line.chars().enumerate() {
-                let active = match chr {
-                    '#' => true,
-                    '.' => false,
-                    oh_no => bail!("unknown char `{}` at ({}, {}).", oh_no, x, y),
-                };
-                if active {
-                    world.insert(Coord3::new(x as i32, y as i32, 0));
-                }
-            }
-        }
+        let world = Grid::<bool>::parse(i)?
+            .iter()
+            .filter(|(_, &active)| active)
+            .map(|((x, y), _)| Coord3::new(x as i32, y as i32, 0))
+            .collect();
 
         Ok(Self { world, time: 0 })
     }
@@ -54,7 +49,7 @@ impl Dimension {
 
             let neighbor_count = active
                 .neighbors()
-                .filter(|n| self.world.contains(&n))
+                .filter(|n| self.world.contains(n))
                 .count();
             if neighbor_count == 2 || neighbor_count == 3 {
                 // activate it!
@@ -73,10 +68,8 @@ impl Dimension {
                     let coord = Coord3::new(x, y, z);
                     if !self.world.contains(&coord) {
                         // ok this is a dead cube! will it wake up?
-                        let neighbor_count = coord
-                            .neighbors()
-                            .filter(|n| self.world.contains(&n))
-                            .count();
+                        let neighbor_count =
+                            coord.neighbors().filter(|n| self.world.contains(n)).count();
                         if neighbor_count == 3 {
                             // it is here!
                             newmap.insert(coord);
@@ -3,37 +3,69 @@ mod dimension4;
 
 use anyhow::Result;
 
-const INPUT: &str = include_str!("input.txt");
+use crate::solution::Solution;
 
-#[test]
-fn part1() -> Result<()> {
-    let mut dimension = dimension3::Dimension::new(INPUT)?;
+pub struct Day17;
+
+impl Solution for Day17 {
+    /// The same starting slice, in 3 and in 4 dimensions
+    type Parsed = (dimension3::Dimension, dimension4::Dimension);
+    type Answer1 = usize;
+    type Answer2 = usize;
 
-    for _ in 0..6 {
-        dimension.step();
+    fn parse(input: &str) -> Result<Self::Parsed> {
+        Ok((
+            dimension3::Dimension::new(input)?,
+            dimension4::Dimension::new(input)?,
+        ))
     }
 
-    let active = dimension.get_world().len();
-    println!("active cubes: {}", active);
+    fn part1((dimension, _): &Self::Parsed) -> Result<usize> {
+        let mut dimension = dimension.clone();
 
-    Ok(())
+        for _ in 0..6 {
+            dimension.step();
+        }
+
+        Ok(dimension.get_world().len())
+    }
+
+    fn part2((_, dimension): &Self::Parsed) -> Result<usize> {
+        // this function looks familiar
+        let mut dimension = dimension.clone();
+
+        for _ in 0..6 {
+            dimension.step();
+        }
 
-    // 245: too low
+        Ok(dimension.get_world().len())
+    }
 }
 
 #[test]
-fn part2() -> Result<()> {
-    // this function looks familiar
-    let mut dimension = dimension4::Dimension::new(INPUT)?;
+fn part1() -> Result<()> {
+    let dimensions = Day17::parse(&test_input!(17))?;
+    let answer = Day17::part1(&dimensions)?;
+    println!("active cubes: {}", answer);
+    check_answer!(17, One, answer);
 
-    for _ in 0..6 {
-        dimension.step();
-    }
+    Ok(())
+}
 
-    let active = dimension.get_world().len();
-    println!("active cubes: {}", active);
+#[test]
+fn part2() -> Result<()> {
+    let dimensions = Day17::parse(&test_input!(17))?;
+    let answer = Day17::part2(&dimensions)?;
+    println!("active cubes: {}", answer);
+    check_answer!(17, Two, answer);
 
     Ok(())
+}
 
-    // 4029: too high
+#[test]
+fn part1_test() -> Result<()> {
+    let dimensions = Day17::parse(".#.\n..#\n###")?;
+    assert_eq!(Day17::part1(&dimensions)?, 112);
+
+    Ok(())
 }
@@ -4,14 +4,35 @@ use parsing::Term;
 
 use anyhow::{anyhow, Context, Result};
 
-const INPUT: &str = include_str!("input.txt");
+use crate::solution::Solution;
+
+pub struct Day18;
+
+impl Solution for Day18 {
+    /// Each line parsed with no precedence, and then with addition first
+    type Parsed = (Vec<Term>, Vec<Term>);
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Parsed> {
+        Ok((
+            parse_lines_communist(input)?,
+            parse_lines_precedentful(input)?,
+        ))
+    }
+
+    fn part1((terms, _): &Self::Parsed) -> Result<u64> {
+        sum_terms(terms)
+    }
+
+    fn part2((_, terms): &Self::Parsed) -> Result<u64> {
+        sum_terms(terms)
+    }
+}
 
 /// Parse a string into a bunch of terms line-by-line.
 fn parse_lines_communist(input: &str) -> Result<Vec<Term>> {
-    input
-        .lines()
-        .map(|line| Term::lex_parse_communist(line))
-        .collect()
+    input.lines().map(Term::lex_parse_communist).collect()
 }
 
 fn parse_lines_precedentful(input: &str) -> Result<Vec<Term>> {
@@ -23,32 +44,33 @@ fn parse_lines_precedentful(input: &str) -> Result<Vec<Term>> {
         .collect()
 }
 
-#[test]
-fn part1() -> Result<()> {
-    let terms = parse_lines_communist(INPUT)?;
+/// Evaluate all the terms and add them up
+fn sum_terms(terms: &[Term]) -> Result<u64> {
     let values = terms
         .iter()
         .map(|term| term.evaluate())
         .collect::<Result<Vec<_>>>()?;
-    let sum: u64 = values.iter().sum();
-    println!("sum: {}", sum);
+    Ok(values.iter().sum())
+}
+
+#[test]
+fn part1() -> Result<()> {
+    let terms = Day18::parse(&test_input!(18))?;
+    let answer = Day18::part1(&terms)?;
+    println!("sum: {}", answer);
+    check_answer!(18, One, answer);
 
     Ok(())
 }
 
 #[test]
 fn part2() -> Result<()> {
-    let terms = parse_lines_precedentful(INPUT)?;
-    let values = terms
-        .iter()
-        .map(|term| term.evaluate())
-        .collect::<Result<Vec<_>>>()?;
-    let sum: u64 = values.iter().sum();
-    println!("sum: {}", sum);
+    let terms = Day18::parse(&test_input!(18))?;
+    let answer = Day18::part2(&terms)?;
+    println!("sum: {}", answer);
+    check_answer!(18, Two, answer);
 
     Ok(())
-
-    // 734848159905949: too high
 }
 
 #[test]
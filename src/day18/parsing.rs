@@ -126,7 +126,7 @@ impl Term {
                     Term::Nothing => *current = Term::Literal(num),
                     Term::Compound(_lhs, _oper, ref mut rhs @ box Term::Nothing) => {
                         // fill the blank right side
-                        *rhs = Box::new(Term::Literal(num))
+                        **rhs = Term::Literal(num)
                     }
                     Term::Compound(..) | Term::EndParen => {
                         bail!("tried to put a compound before a literal")
@@ -155,7 +155,7 @@ impl Term {
                     match current {
                         Term::Nothing => *current = finished,
                         Term::Compound(_lhs, _oper, ref mut rhs @ box Term::Nothing) => {
-                            *rhs = Box::new(finished)
+                            **rhs = finished
                         }
                         Term::Compound(..) | Term::EndParen => {
                             bail!("tried to put two compounds together")
@@ -238,7 +238,7 @@ impl Term {
                     Term::Nothing | Term::EndParen => *current = Term::Literal(num),
                     Term::Compound(_lhs, _oper, ref mut rhs @ box Term::Nothing) => {
                         // fill the blank right side
-                        *rhs = Box::new(Term::Literal(num));
+                        **rhs = Term::Literal(num);
                     }
                     Term::Compound(..) => bail!("tried to put a compound before a literal"),
                     Term::Literal(..) => bail!("tried to put two literals together"),
@@ -301,7 +301,7 @@ impl Term {
                         Some(current) => match current {
                             Term::Nothing | Term::EndParen => *current = finished,
                             Term::Compound(_lhs, _oper, ref mut rhs @ box Term::Nothing) => {
-                                *rhs = Box::new(finished)
+                                **rhs = finished
                             }
                             Term::Compound(..) => bail!("tried to put two compounds together"),
                             Term::Literal(..) => bail!("tried to put a literal before a compound"),
@@ -334,7 +334,7 @@ impl Term {
             //     next.pretty_print()
             // );
             if let Term::Compound(_, _, ref mut rhs @ box Term::Nothing) = next {
-                *rhs = Box::new(top);
+                **rhs = top;
             } else if *next == Term::Nothing || *next == Term::EndParen {
                 // Just merge it on down
                 *next = top;
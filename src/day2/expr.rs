@@ -0,0 +1,595 @@
+//! A little language for writing password policies.
+//!
+//! A policy is a condition on the password, and can use the numbers and letter from its line:
+//!
+//! ```text
+//! count(letter) >= num1 and count(letter) <= num2   ; part 1
+//! at(num1) == letter xor at(num2) == letter         ; part 2
+//! len >= 8 and count('x') == 0 and not (at(1) == 'a' or at(1) == 'b')
+//! ```
+//!
+//! - Numbers are `num1`, `num2`, `len` (the length of the password), `count(C)`
+//!   (how many times a character appears) and plain numbers.
+//! - Characters are `letter`, `'c'` and `at(N)` (the character at a 1-based position).
+//!   Positions past the end of the password aren't equal to anything.
+//! - Numbers compare with `==`, `!=`, `<`, `<=`, `>` and `>=`; characters only with `==` and `!=`.
+//! - Conditions combine with `not`, `and`, `xor` and `or`, tightest first, and parentheses.
+
+use std::{
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+
+use anyhow::{anyhow, bail, Result};
+
+use super::{
+    policy::{char_at, describe_char, PasswordPolicy},
+    Line,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token<'a> {
+    Ident(&'a str),
+    Number(usize),
+    Char(char),
+    Compare(CmpOp),
+    OpenParen,
+    CloseParen,
+}
+
+impl Display for Token<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Ident(it) => write!(f, "`{}`", it),
+            Token::Number(it) => write!(f, "`{}`", it),
+            Token::Char(it) => write!(f, "`'{}'`", it),
+            Token::Compare(it) => write!(f, "`{}`", it),
+            Token::OpenParen => write!(f, "`(`"),
+            Token::CloseParen => write!(f, "`)`"),
+        }
+    }
+}
+
+/// Split a policy up into tokens, each with its 1-based column.
+fn lex(i: &str) -> Result<Vec<(Token<'_>, usize)>> {
+    let mut tokens = Vec::new();
+    let mut chars = i.char_indices().enumerate().peekable();
+    while let Some((column, (idx, c))) = chars.next() {
+        let column = column + 1;
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::OpenParen,
+            ')' => Token::CloseParen,
+            '\'' => {
+                let c = match chars.next() {
+                    Some((_, (_, c))) => c,
+                    None => bail!("column {}: unfinished character", column),
+                };
+                match chars.next() {
+                    Some((_, (_, '\''))) => Token::Char(c),
+                    _ => bail!("column {}: expected a `'` to end the character", column),
+                }
+            }
+            '=' | '!' | '<' | '>' => {
+                let equals = chars.next_if(|&(_, (_, c))| c == '=').is_some();
+                Token::Compare(match (c, equals) {
+                    ('=', true) => CmpOp::Eq,
+                    ('!', true) => CmpOp::Ne,
+                    ('<', false) => CmpOp::Lt,
+                    ('<', true) => CmpOp::Le,
+                    ('>', false) => CmpOp::Gt,
+                    ('>', true) => CmpOp::Ge,
+                    _ => bail!("column {}: `{}` needs to be followed by `=`", column, c),
+                })
+            }
+            c if c.is_ascii_digit() => {
+                let mut end = idx + 1;
+                while let Some((_, (idx, c))) = chars.next_if(|(_, (_, c))| c.is_ascii_digit()) {
+                    end = idx + c.len_utf8();
+                }
+                Token::Number(
+                    i[idx..end]
+                        .parse()
+                        .map_err(|e| anyhow!("column {}: {}", column, e))?,
+                )
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let mut end = idx + 1;
+                while let Some((_, (idx, c))) =
+                    chars.next_if(|(_, (_, c))| c.is_ascii_alphanumeric() || *c == '_')
+                {
+                    end = idx + c.len_utf8();
+                }
+                Token::Ident(&i[idx..end])
+            }
+            oh_no => bail!("column {}: unexpected character `{}`", column, oh_no),
+        };
+        tokens.push((token, column));
+    }
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Display for CmpOp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            CmpOp::Eq => "==",
+            CmpOp::Ne => "!=",
+            CmpOp::Lt => "<",
+            CmpOp::Le => "<=",
+            CmpOp::Gt => ">",
+            CmpOp::Ge => ">=",
+        })
+    }
+}
+
+/// Something that can be compared
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Value {
+    Literal(usize),
+    Num1,
+    Num2,
+    Len,
+    Count(Box<Value>),
+    Char(char),
+    Letter,
+    At(Box<Value>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Number,
+    Char,
+}
+
+/// What a [`Value`] turned out to be on a line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Evaluated {
+    Number(usize),
+    /// None if it's past the end of the password
+    Char(Option<char>),
+}
+
+impl Display for Evaluated {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Evaluated::Number(it) => write!(f, "{}", it),
+            Evaluated::Char(it) => f.write_str(&describe_char(*it)),
+        }
+    }
+}
+
+impl Value {
+    fn kind(&self) -> Kind {
+        match self {
+            Value::Literal(_) | Value::Num1 | Value::Num2 | Value::Len | Value::Count(_) => {
+                Kind::Number
+            }
+            Value::Char(_) | Value::Letter | Value::At(_) => Kind::Char,
+        }
+    }
+
+    /// Does this always evaluate to what it looks like?
+    fn is_literal(&self) -> bool {
+        matches!(self, Value::Literal(_) | Value::Char(_))
+    }
+
+    fn eval(&self, line: &Line) -> Evaluated {
+        let rule = line.policy();
+        match self {
+            Value::Literal(it) => Evaluated::Number(*it),
+            Value::Num1 => Evaluated::Number(rule.num1()),
+            Value::Num2 => Evaluated::Number(rule.num2()),
+            Value::Len => Evaluated::Number(line.password().chars().count()),
+            Value::Count(c) => {
+                let c = c.eval(line);
+                Evaluated::Number(
+                    line.password()
+                        .chars()
+                        .filter(|&it| Evaluated::Char(Some(it)) == c)
+                        .count(),
+                )
+            }
+            Value::Char(it) => Evaluated::Char(Some(*it)),
+            Value::Letter => Evaluated::Char(Some(rule.letter())),
+            Value::At(pos) => match pos.eval(line) {
+                Evaluated::Number(pos) => Evaluated::Char(char_at(line.password(), pos)),
+                // the parser checks the kinds
+                Evaluated::Char(_) => unreachable!(),
+            },
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Literal(it) => write!(f, "{}", it),
+            Value::Num1 => write!(f, "num1"),
+            Value::Num2 => write!(f, "num2"),
+            Value::Len => write!(f, "len"),
+            Value::Count(it) => write!(f, "count({})", it),
+            Value::Char(it) => write!(f, "'{}'", it),
+            Value::Letter => write!(f, "letter"),
+            Value::At(it) => write!(f, "at({})", it),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Expr {
+    Compare(Value, CmpOp, Value),
+    Not(Box<Expr>),
+    And(Vec<Expr>),
+    Xor(Box<Expr>, Box<Expr>),
+    Or(Vec<Expr>),
+}
+
+impl Expr {
+    /// How tightly this binds, for deciding where parentheses go
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Or(_) => 0,
+            Expr::Xor(..) => 1,
+            Expr::And(_) => 2,
+            Expr::Not(_) | Expr::Compare(..) => 3,
+        }
+    }
+
+    fn holds(&self, line: &Line) -> bool {
+        match self {
+            Expr::Compare(lhs, op, rhs) => {
+                let (lhs, rhs) = (lhs.eval(line), rhs.eval(line));
+                match (lhs, rhs) {
+                    (Evaluated::Number(lhs), Evaluated::Number(rhs)) => match op {
+                        CmpOp::Eq => lhs == rhs,
+                        CmpOp::Ne => lhs != rhs,
+                        CmpOp::Lt => lhs < rhs,
+                        CmpOp::Le => lhs <= rhs,
+                        CmpOp::Gt => lhs > rhs,
+                        CmpOp::Ge => lhs >= rhs,
+                    },
+                    (Evaluated::Char(lhs), Evaluated::Char(rhs)) => {
+                        // past the end isn't equal to anything, not even itself
+                        let equal = lhs.is_some() && lhs == rhs;
+                        match op {
+                            CmpOp::Eq => equal,
+                            CmpOp::Ne => !equal,
+                            _ => unreachable!(),
+                        }
+                    }
+                    _ => unreachable!(),
+                }
+            }
+            Expr::Not(it) => !it.holds(line),
+            Expr::And(them) => them.iter().all(|it| it.holds(line)),
+            Expr::Xor(lhs, rhs) => lhs.holds(line) != rhs.holds(line),
+            Expr::Or(them) => them.iter().any(|it| it.holds(line)),
+        }
+    }
+
+    /// Explain why this doesn't hold, or None if it does.
+    fn explain(&self, line: &Line) -> Option<String> {
+        if self.holds(line) {
+            return None;
+        }
+        Some(match self {
+            Expr::Compare(lhs, op, rhs) => {
+                let mut out = format!(
+                    "{} is {}, but it has to be {} {}",
+                    lhs,
+                    lhs.eval(line),
+                    op,
+                    rhs
+                );
+                if !rhs.is_literal() {
+                    out.push_str(&format!(" ({})", rhs.eval(line)));
+                }
+                out
+            }
+            Expr::Not(it) => format!("`{}` holds", it),
+            Expr::And(them) => them
+                .iter()
+                .filter_map(|it| it.explain(line))
+                .collect::<Vec<_>>()
+                .join("; "),
+            Expr::Xor(lhs, rhs) => match lhs.explain(line) {
+                // they both held
+                None => format!("`{}` and `{}` both hold", lhs, rhs),
+                Some(reason) => format!(
+                    "neither side of the xor holds: {}; {}",
+                    reason,
+                    // it doesn't hold either if we got here
+                    rhs.explain(line).unwrap_or_default()
+                ),
+            },
+            Expr::Or(them) => format!(
+                "none of the options hold: {}",
+                them.iter()
+                    .filter_map(|it| it.explain(line))
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            ),
+        })
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        /// Write a sub-expression, with parentheses if it binds looser than `min`
+        fn sub(f: &mut Formatter<'_>, expr: &Expr, min: u8) -> std::fmt::Result {
+            if expr.precedence() < min {
+                write!(f, "({})", expr)
+            } else {
+                write!(f, "{}", expr)
+            }
+        }
+        fn list(f: &mut Formatter<'_>, them: &[Expr], joiner: &str, min: u8) -> std::fmt::Result {
+            for (idx, it) in them.iter().enumerate() {
+                if idx != 0 {
+                    f.write_str(joiner)?;
+                }
+                sub(f, it, min)?;
+            }
+            Ok(())
+        }
+
+        match self {
+            Expr::Compare(lhs, op, rhs) => write!(f, "{} {} {}", lhs, op, rhs),
+            Expr::Not(it) => {
+                f.write_str("not ")?;
+                sub(f, it, 3)
+            }
+            Expr::And(them) => list(f, them, " and ", 3),
+            Expr::Xor(lhs, rhs) => {
+                sub(f, lhs, 1)?;
+                f.write_str(" xor ")?;
+                sub(f, rhs, 2)
+            }
+            Expr::Or(them) => list(f, them, " or ", 1),
+        }
+    }
+}
+
+/// Recursive descent over the tokens
+struct Parser<'a> {
+    tokens: Vec<(Token<'a>, usize)>,
+    pos: usize,
+    /// Column just past the end, for errors about running out
+    end: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.pos).map(|&(tok, _)| tok)
+    }
+
+    fn next(&mut self, expected: &str) -> Result<Token<'a>> {
+        match self.tokens.get(self.pos) {
+            Some(&(tok, _)) => {
+                self.pos += 1;
+                Ok(tok)
+            }
+            None => bail!("column {}: expected {}, found the end", self.end, expected),
+        }
+    }
+
+    /// Make an error about the token we just took
+    fn unexpected(&self, expected: &str) -> anyhow::Error {
+        let (tok, column) = self.tokens[self.pos - 1];
+        anyhow!("column {}: expected {}, found {}", column, expected, tok)
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if self.peek() == Some(Token::Ident(keyword)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr> {
+        let mut them = vec![self.xor()?];
+        while self.eat_keyword("or") {
+            them.push(self.xor()?);
+        }
+        Ok(if them.len() == 1 {
+            them.pop().unwrap()
+        } else {
+            Expr::Or(them)
+        })
+    }
+
+    fn xor(&mut self) -> Result<Expr> {
+        let mut lhs = self.and()?;
+        while self.eat_keyword("xor") {
+            lhs = Expr::Xor(Box::new(lhs), Box::new(self.and()?));
+        }
+        Ok(lhs)
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        let mut them = vec![self.unary()?];
+        while self.eat_keyword("and") {
+            them.push(self.unary()?);
+        }
+        Ok(if them.len() == 1 {
+            them.pop().unwrap()
+        } else {
+            Expr::And(them)
+        })
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        if self.eat_keyword("not") {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        if self.peek() == Some(Token::OpenParen) {
+            self.pos += 1;
+            let inner = self.or()?;
+            return match self.next("`)`")? {
+                Token::CloseParen => Ok(inner),
+                _ => Err(self.unexpected("`)`")),
+            };
+        }
+
+        let lhs_column = self.tokens.get(self.pos).map_or(self.end, |&(_, col)| col);
+        let lhs = self.value()?;
+        let op = match self.next("a comparison")? {
+            Token::Compare(op) => op,
+            _ => return Err(self.unexpected("a comparison")),
+        };
+        let rhs = self.value()?;
+        if lhs.kind() != rhs.kind() {
+            bail!(
+                "column {}: can't compare `{}` with `{}`, one's a number and one's a character",
+                lhs_column,
+                lhs,
+                rhs
+            );
+        }
+        if lhs.kind() == Kind::Char && !matches!(op, CmpOp::Eq | CmpOp::Ne) {
+            bail!(
+                "column {}: characters can only be compared with `==` and `!=`, not `{}`",
+                lhs_column,
+                op
+            );
+        }
+        Ok(Expr::Compare(lhs, op, rhs))
+    }
+
+    fn value(&mut self) -> Result<Value> {
+        Ok(match self.next("a value")? {
+            Token::Number(it) => Value::Literal(it),
+            Token::Char(it) => Value::Char(it),
+            Token::Ident("num1") => Value::Num1,
+            Token::Ident("num2") => Value::Num2,
+            Token::Ident("len") => Value::Len,
+            Token::Ident("letter") => Value::Letter,
+            Token::Ident(func @ "count") | Token::Ident(func @ "at") => {
+                let column = self.tokens[self.pos - 1].1;
+                match self.next("`(`")? {
+                    Token::OpenParen => {}
+                    _ => return Err(self.unexpected("`(`")),
+                }
+                let arg = self.value()?;
+                match self.next("`)`")? {
+                    Token::CloseParen => {}
+                    _ => return Err(self.unexpected("`)`")),
+                }
+                let (wanted, value) = if func == "count" {
+                    (Kind::Char, Value::Count(Box::new(arg.clone())))
+                } else {
+                    (Kind::Number, Value::At(Box::new(arg.clone())))
+                };
+                if arg.kind() != wanted {
+                    bail!(
+                        "column {}: `{}` takes a {}, but `{}` isn't one",
+                        column,
+                        func,
+                        if wanted == Kind::Char {
+                            "character"
+                        } else {
+                            "number"
+                        },
+                        arg
+                    );
+                }
+                value
+            }
+            _ => return Err(self.unexpected("a value")),
+        })
+    }
+}
+
+/// A policy written in the little language.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyExpr {
+    expr: Expr,
+}
+
+impl FromStr for PolicyExpr {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parser = Parser {
+            tokens: lex(s)?,
+            pos: 0,
+            end: s.chars().count() + 1,
+        };
+        let expr = parser.or()?;
+        if parser.pos < parser.tokens.len() {
+            parser.pos += 1;
+            return Err(parser.unexpected("the end"));
+        }
+        Ok(Self { expr })
+    }
+}
+
+impl Display for PolicyExpr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.expr)
+    }
+}
+
+impl PasswordPolicy for PolicyExpr {
+    fn violation(&self, line: &Line) -> Option<String> {
+        self.expr.explain(line)
+    }
+
+    fn check(&self, line: &Line) -> bool {
+        self.expr.holds(line)
+    }
+}
+
+#[test]
+fn test_parse() -> Result<()> {
+    for src in &[
+        "count(letter) >= num1 and count(letter) <= num2",
+        "at(num1) == letter xor at(num2) == letter",
+        "len >= 8 and count('x') == 0 and not (at(1) == 'a' or at(1) == 'b')",
+        "len > 1 xor len > 2 xor (len > 3 or len > 4)",
+        "len > 1 xor (len > 2 xor len > 3)",
+    ] {
+        let policy = src.parse::<PolicyExpr>()?;
+        assert_eq!(&policy.to_string(), src);
+    }
+    let policy = "len>1 and(len<5)".parse::<PolicyExpr>()?;
+    assert_eq!(policy.to_string(), "len > 1 and len < 5");
+
+    let err = |src: &str| src.parse::<PolicyExpr>().unwrap_err().to_string();
+    assert_eq!(
+        err("len >= 8 and"),
+        "column 13: expected a value, found the end"
+    );
+    assert_eq!(err("len = 8"), "column 5: `=` needs to be followed by `=`");
+    assert_eq!(
+        err("len == letter"),
+        "column 1: can't compare `len` with `letter`, one's a number and one's a character"
+    );
+    assert_eq!(
+        err("letter < 'a'"),
+        "column 1: characters can only be compared with `==` and `!=`, not `<`"
+    );
+    assert_eq!(
+        err("len > 1 len"),
+        "column 9: expected the end, found `len`"
+    );
+    assert_eq!(
+        err("count(3) > 1"),
+        "column 1: `count` takes a character, but `3` isn't one"
+    );
+    assert!("(len > 1".parse::<PolicyExpr>().is_err());
+    assert!("len > 'ab'".parse::<PolicyExpr>().is_err());
+
+    Ok(())
+}
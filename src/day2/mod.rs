@@ -1,52 +1,74 @@
-use once_cell::sync::Lazy;
-use regex::Regex;
-use std::{num::ParseIntError, str::FromStr};
+use anyhow::Result;
+use std::str::FromStr;
 
-const INPUT: &str = include_str!("input.txt");
+use crate::solution::Solution;
 
-struct Line {
+mod expr;
+mod parsing;
+mod policy;
+
+pub use expr::PolicyExpr;
+pub use parsing::{parse_lines, parse_lines_lenient, Expected, ParseError};
+pub use policy::{diagnose, CountInRange, Diagnostic, ExactlyOnePosition, PasswordPolicy};
+
+pub struct Day2;
+
+impl Solution for Day2 {
+    type Parsed = Vec<Line>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Parsed> {
+        Ok(parse_lines(input)?)
+    }
+
+    fn part1(lines: &Self::Parsed) -> Result<usize> {
+        Ok(lines.iter().filter(|line| CountInRange.check(line)).count())
+    }
+
+    fn part2(lines: &Self::Parsed) -> Result<usize> {
+        Ok(lines
+            .iter()
+            .filter(|line| ExactlyOnePosition.check(line))
+            .count())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Line {
     policy: Policy,
     input: String,
+    /// 1-based line number it came from
+    line: usize,
 }
 
 impl Line {
-    /// Check if this line is valid wrt part 1
-    fn check_part1(&self) -> bool {
-        let count = self
-            .input
-            .chars()
-            .filter(|&c| c == self.policy.letter)
-            .count();
-        self.policy.num1 <= count && count <= self.policy.num2
+    /// The numbers and letter at the start of the line
+    pub fn policy(&self) -> &Policy {
+        &self.policy
     }
 
-    /// Check if this line is valid wrt part 2
-    fn check_part2(&self) -> bool {
-        let chars = self.input.chars().collect::<Vec<_>>();
-        let num1_matches = chars[self.policy.num1 - 1] == self.policy.letter;
-        let num2_matches = chars[self.policy.num2 - 1] == self.policy.letter;
-        num1_matches != num2_matches
+    /// The password at the end of the line
+    pub fn password(&self) -> &str {
+        &self.input
+    }
+
+    /// The 1-based line number this came from
+    pub fn number(&self) -> usize {
+        self.line
     }
 }
 
 impl FromStr for Line {
-    type Err = String;
+    type Err = ParseError;
+    /// Parse a line on its own, as if it were line 1
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        static REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"(\d+)-(\d+) (\w): (\w*)"#).unwrap());
-        let caps = REGEX
-            .captures(s)
-            .ok_or_else(|| String::from("Regex found no captures"))?;
-        let min_count = caps[1].parse().map_err(|e: ParseIntError| e.to_string())?;
-        let max_count = caps[2].parse().map_err(|e: ParseIntError| e.to_string())?;
-        let letter = caps[3].chars().next().unwrap();
-        Ok(Line {
-            policy: Policy::new(min_count, max_count, letter),
-            input: String::from(&caps[4]),
-        })
+        parsing::parse_line(s, 1)
     }
 }
 
-struct Policy {
+#[derive(Debug, Clone)]
+pub struct Policy {
     /// The first number in a line
     num1: usize,
     /// The second number in a line
@@ -62,26 +84,90 @@ impl Policy {
             letter,
         }
     }
+
+    pub fn num1(&self) -> usize {
+        self.num1
+    }
+
+    pub fn num2(&self) -> usize {
+        self.num2
+    }
+
+    pub fn letter(&self) -> char {
+        self.letter
+    }
+}
+
+#[test]
+fn part1() -> Result<()> {
+    let lines = Day2::parse(&test_input!(2))?;
+    let answer = Day2::part1(&lines)?;
+    println!("count: {}", answer);
+    check_answer!(2, One, answer);
+
+    Ok(())
 }
 
 #[test]
-fn part1() {
-    let lines: Vec<Line> = INPUT
-        .lines()
-        .map(FromStr::from_str)
-        .collect::<Result<_, _>>()
-        .unwrap();
-    let count = lines.iter().filter(|line| line.check_part1()).count();
-    println!("count: {}", count);
+fn part2() -> Result<()> {
+    let lines = Day2::parse(&test_input!(2))?;
+    let answer = Day2::part2(&lines)?;
+    println!("count: {}", answer);
+    check_answer!(2, Two, answer);
+
+    Ok(())
 }
 
 #[test]
-fn part2() {
-    let lines: Vec<Line> = INPUT
-        .lines()
-        .map(FromStr::from_str)
-        .collect::<Result<_, _>>()
-        .unwrap();
-    let count = lines.iter().filter(|line| line.check_part2()).count();
-    println!("count: {}", count);
+fn test_policies() -> Result<()> {
+    let lines = Day2::parse("1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc\n3-30 c: abc")?;
+    assert_eq!(Day2::part1(&lines)?, 2);
+    assert_eq!(Day2::part2(&lines)?, 2);
+
+    // the expressions do the same thing as the built-in ones
+    let part1 = "count(letter) >= num1 and count(letter) <= num2".parse::<PolicyExpr>()?;
+    let part2 = "at(num1) == letter xor at(num2) == letter".parse::<PolicyExpr>()?;
+    for line in lines.iter() {
+        assert_eq!(part1.check(line), CountInRange.check(line));
+        assert_eq!(part2.check(line), ExactlyOnePosition.check(line));
+    }
+
+    let report = diagnose(&lines, &ExactlyOnePosition)
+        .iter()
+        .map(|it| it.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        report,
+        vec![
+            "line 2: `cdefg`: neither position 1 ('c') nor position 3 ('e') is 'b'",
+            "line 3: `ccccccccc`: positions 2 and 9 are both 'c'",
+        ]
+    );
+    let report = diagnose(&lines, &part2)
+        .iter()
+        .map(|it| it.reason.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        report,
+        vec![
+            "neither side of the xor holds: at(num1) is 'c', but it has to be == letter ('b'); at(num2) is 'e', but it has to be == letter ('b')",
+            "`at(num1) == letter` and `at(num2) == letter` both hold",
+        ]
+    );
+
+    let strict = "len >= 5 and count('e') == 0".parse::<PolicyExpr>()?;
+    let report = diagnose(&lines, &strict)
+        .iter()
+        .map(|it| it.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        report,
+        vec![
+            "line 1: `abcde`: count('e') is 1, but it has to be == 0",
+            "line 2: `cdefg`: count('e') is 1, but it has to be == 0",
+            "line 4: `abc`: len is 3, but it has to be >= 5",
+        ]
+    );
+
+    Ok(())
 }
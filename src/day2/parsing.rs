@@ -0,0 +1,245 @@
+use std::{
+    error::Error,
+    fmt::{Display, Formatter},
+    iter::Peekable,
+    str::CharIndices,
+};
+
+use super::{Line, Policy};
+
+/// The bit of a line the parser was looking for when it went wrong
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Expected {
+    Number,
+    /// It was a number, but too big of one
+    SmallerNumber,
+    Dash,
+    Space,
+    Letter,
+    Colon,
+    /// The password ended but the line didn't
+    End,
+}
+
+impl Display for Expected {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Expected::Number => "a number",
+            Expected::SmallerNumber => "a number that fits in a usize",
+            Expected::Dash => "`-`",
+            Expected::Space => "a space",
+            Expected::Letter => "a letter",
+            Expected::Colon => "`:`",
+            Expected::End => "the end of the line",
+        })
+    }
+}
+
+/// A line that isn't in the form `1-3 a: abcde`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ParseError {
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based column, in characters
+    pub column: usize,
+    pub expected: Expected,
+    /// What was there instead, or None if the line ran out
+    pub found: Option<char>,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: expected {}, found ",
+            self.line, self.column, self.expected
+        )?;
+        match self.found {
+            Some(c) => write!(f, "{:?}", c),
+            None => write!(f, "the end of the line"),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// Is this ok in a password or as a letter?
+/// (Same as `\w` in a regex.)
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Walks along a line keeping track of the column
+struct Cursor<'a> {
+    s: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+    line: usize,
+    /// 1-based column of the next character
+    column: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn error(&mut self, expected: Expected) -> ParseError {
+        ParseError {
+            line: self.line,
+            column: self.column,
+            expected,
+            found: self.chars.peek().map(|&(_, c)| c),
+        }
+    }
+
+    /// Take the next character if it's this one
+    fn expect(&mut self, wanted: char, expected: Expected) -> Result<(), ParseError> {
+        match self.chars.peek() {
+            Some(&(_, c)) if c == wanted => {
+                self.chars.next();
+                self.column += 1;
+                Ok(())
+            }
+            _ => Err(self.error(expected)),
+        }
+    }
+
+    /// Take characters while they match, returning them
+    fn take_while(&mut self, pred: impl Fn(char) -> bool) -> &'a str {
+        let start = match self.chars.peek() {
+            Some(&(idx, _)) => idx,
+            None => self.s.len(),
+        };
+        let mut end = start;
+        while let Some((idx, c)) = self.chars.next_if(|&(_, c)| pred(c)) {
+            end = idx + c.len_utf8();
+            self.column += 1;
+        }
+        &self.s[start..end]
+    }
+
+    fn number(&mut self) -> Result<usize, ParseError> {
+        let column = self.column;
+        let digits = self.take_while(|c| c.is_ascii_digit());
+        if digits.is_empty() {
+            return Err(self.error(Expected::Number));
+        }
+        // the only way this can fail now is by being too big
+        digits.parse().map_err(|_| ParseError {
+            line: self.line,
+            column,
+            expected: Expected::SmallerNumber,
+            found: digits.chars().next(),
+        })
+    }
+}
+
+/// Parse a line in the form `1-3 a: abcde`, with nothing before or after it.
+///
+/// `line` is the 1-based line number, for errors.
+pub(super) fn parse_line(s: &str, line: usize) -> Result<Line, ParseError> {
+    let mut cursor = Cursor {
+        s,
+        chars: s.char_indices().peekable(),
+        line,
+        column: 1,
+    };
+
+    let num1 = cursor.number()?;
+    cursor.expect('-', Expected::Dash)?;
+    let num2 = cursor.number()?;
+    cursor.expect(' ', Expected::Space)?;
+    let letter = match cursor.chars.peek() {
+        Some(&(_, c)) if is_word(c) => {
+            cursor.chars.next();
+            cursor.column += 1;
+            c
+        }
+        _ => return Err(cursor.error(Expected::Letter)),
+    };
+    cursor.expect(':', Expected::Colon)?;
+    cursor.expect(' ', Expected::Space)?;
+    let password = cursor.take_while(is_word).to_string();
+    if cursor.chars.peek().is_some() {
+        return Err(cursor.error(Expected::End));
+    }
+
+    Ok(Line {
+        policy: Policy::new(num1, num2, letter),
+        input: password,
+        line,
+    })
+}
+
+/// Parse every line, stopping at the first bad one.
+pub fn parse_lines(input: &str) -> Result<Vec<Line>, ParseError> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| parse_line(line, idx + 1))
+        .collect()
+}
+
+/// Parse every line, returning all the good ones and an error for each bad one
+/// instead of giving up at the first.
+pub fn parse_lines_lenient(input: &str) -> (Vec<Line>, Vec<ParseError>) {
+    let mut lines = Vec::new();
+    let mut errors = Vec::new();
+    for (idx, line) in input.lines().enumerate() {
+        match parse_line(line, idx + 1) {
+            Ok(it) => lines.push(it),
+            Err(e) => errors.push(e),
+        }
+    }
+    (lines, errors)
+}
+
+#[test]
+fn test_parse_errors() {
+    let line = parse_line("1-3 a: abcde", 1).unwrap();
+    assert_eq!(
+        (
+            line.policy().num1(),
+            line.policy().num2(),
+            line.policy().letter(),
+            line.password()
+        ),
+        (1, 3, 'a', "abcde")
+    );
+    assert_eq!(parse_line("1-3 a: ", 1).unwrap().password(), "");
+
+    let err = |s: &str| {
+        let e = parse_line(s, 7).unwrap_err();
+        (e.column, e.expected, e.found)
+    };
+    assert_eq!(err(""), (1, Expected::Number, None));
+    assert_eq!(err("x1-3 a: abc"), (1, Expected::Number, Some('x')));
+    assert_eq!(err("1 3 a: abc"), (2, Expected::Dash, Some(' ')));
+    assert_eq!(err("1-3a: abc"), (4, Expected::Space, Some('a')));
+    assert_eq!(err("1-3 : abc"), (5, Expected::Letter, Some(':')));
+    // this used to silently become `a: bc`
+    assert_eq!(err("1-3 ab: abc"), (6, Expected::Colon, Some('b')));
+    assert_eq!(err("1-3 a:abc"), (7, Expected::Space, Some('a')));
+    // and this used to silently drop the `!`
+    assert_eq!(err("1-3 a: ab!c"), (10, Expected::End, Some('!')));
+    assert_eq!(err("1-3 a: abc "), (11, Expected::End, Some(' ')));
+    assert_eq!(
+        err("1-99999999999999999999999 a: abc"),
+        (3, Expected::SmallerNumber, Some('9'))
+    );
+    assert_eq!(
+        parse_line("1-3 a:abc", 7).unwrap_err().to_string(),
+        "line 7, column 7: expected a space, found 'a'"
+    );
+
+    let input = "1-3 a: abcde\n1-3 b cdefg\r\n2-9 c: ccccccccc\r\n\n";
+    assert_eq!(parse_lines(input).unwrap_err().line, 2);
+    let (lines, errors) = parse_lines_lenient(input);
+    assert_eq!(
+        lines.iter().map(|it| it.number()).collect::<Vec<_>>(),
+        [1, 3]
+    );
+    assert_eq!(
+        errors
+            .iter()
+            .map(|e| (e.line, e.expected))
+            .collect::<Vec<_>>(),
+        [(2, Expected::Colon), (4, Expected::Number)]
+    );
+}
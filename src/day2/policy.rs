@@ -0,0 +1,121 @@
+use std::fmt::{Display, Formatter};
+
+use super::Line;
+
+/// A way of deciding whether the password on a line is ok.
+pub trait PasswordPolicy {
+    /// Explain why the password on this line breaks the policy,
+    /// or return None if it's fine.
+    fn violation(&self, line: &Line) -> Option<String>;
+
+    /// Is the password on this line ok?
+    fn check(&self, line: &Line) -> bool {
+        self.violation(line).is_none()
+    }
+}
+
+/// The policy from part 1:
+/// the letter has to appear between `num1` and `num2` times, inclusive.
+#[derive(Debug, Copy, Clone)]
+pub struct CountInRange;
+
+impl PasswordPolicy for CountInRange {
+    fn violation(&self, line: &Line) -> Option<String> {
+        let rule = line.policy();
+        let count = line
+            .password()
+            .chars()
+            .filter(|&c| c == rule.letter())
+            .count();
+        if rule.num1() <= count && count <= rule.num2() {
+            None
+        } else {
+            Some(format!(
+                "'{}' appears {} time(s), but it has to appear {} to {} times",
+                rule.letter(),
+                count,
+                rule.num1(),
+                rule.num2()
+            ))
+        }
+    }
+}
+
+/// The policy from part 2:
+/// exactly one of the (1-based) positions `num1` and `num2` has to be the letter.
+/// Positions past the end of the password don't count as the letter.
+#[derive(Debug, Copy, Clone)]
+pub struct ExactlyOnePosition;
+
+impl PasswordPolicy for ExactlyOnePosition {
+    fn violation(&self, line: &Line) -> Option<String> {
+        let rule = line.policy();
+        let first = char_at(line.password(), rule.num1());
+        let second = char_at(line.password(), rule.num2());
+        match (first == Some(rule.letter()), second == Some(rule.letter())) {
+            (true, false) | (false, true) => None,
+            (true, true) => Some(format!(
+                "positions {} and {} are both '{}'",
+                rule.num1(),
+                rule.num2(),
+                rule.letter()
+            )),
+            (false, false) => Some(format!(
+                "neither position {} ({}) nor position {} ({}) is '{}'",
+                rule.num1(),
+                describe_char(first),
+                rule.num2(),
+                describe_char(second),
+                rule.letter()
+            )),
+        }
+    }
+}
+
+/// Get the character at a 1-based position, or None if it's out of range.
+pub(super) fn char_at(password: &str, position: usize) -> Option<char> {
+    position
+        .checked_sub(1)
+        .and_then(|idx| password.chars().nth(idx))
+}
+
+/// Write out a character we might have gotten from [`char_at`]
+pub(super) fn describe_char(c: Option<char>) -> String {
+    match c {
+        Some(c) => format!("'{}'", c),
+        None => String::from("past the end"),
+    }
+}
+
+/// Why a line's password got rejected
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Diagnostic {
+    /// 1-based line number
+    pub line: usize,
+    pub password: String,
+    pub reason: String,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}: `{}`: {}",
+            self.line, self.password, self.reason
+        )
+    }
+}
+
+/// Check every line against a policy, explaining each one that fails.
+pub fn diagnose(lines: &[Line], policy: &dyn PasswordPolicy) -> Vec<Diagnostic> {
+    lines
+        .iter()
+        .filter_map(|line| {
+            policy.violation(line).map(|reason| Diagnostic {
+                line: line.number(),
+                password: line.password().to_string(),
+                reason,
+            })
+        })
+        .collect()
+}
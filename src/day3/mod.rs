@@ -1,89 +1,116 @@
-const INPUT: &str = include_str!("input.txt");
+use anyhow::{anyhow, Result};
+
+use crate::{
+    grid::{Edges, Grid},
+    solution::Solution,
+};
+
+mod render;
+mod traversal;
+
+pub use traversal::{Path, Slope, Step};
+
+/// The slopes part 2 wants checked, as (right, down)
+const PART2_SLOPES: [(isize, usize); 5] = [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)];
+
+pub struct Day3;
+
+impl Solution for Day3 {
+    type Parsed = Forest;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Forest> {
+        Forest::new(input)
+    }
+
+    fn part1(forest: &Forest) -> Result<usize> {
+        Ok(forest.traverse(Slope::new(3, 1)?))
+    }
+
+    fn part2(forest: &Forest) -> Result<usize> {
+        let mut product = 1usize;
+        for &(right, down) in PART2_SLOPES.iter() {
+            product = product
+                .checked_mul(forest.traverse(Slope::new(right, down)?))
+                .ok_or_else(|| anyhow!("the product of the trees hit overflowed"))?;
+        }
+        Ok(product)
+    }
+}
 
 /// A forest full of trees.
 /// Internally only stores what is in the puzzle input; indexing it
 /// gets the proper trees if they're out of bounds.
-struct Forest {
-    /// indexed `[y][x]`.
-    ///
-    /// `true` = a tree is here
-    trees: Vec<Vec<bool>>,
-    /// Width of the forest disregarding looping
-    width: usize,
+pub struct Forest {
+    /// `true` = a tree is here.
+    /// Wraps left-right but not up-down.
+    trees: Grid<bool>,
 }
 
 impl Forest {
     /// Make a new Forest from the input
-    fn new(input: &str) -> Self {
-        let mut width = None;
-        let trees = input
-            .lines()
-            .map(|line| {
-                if width.is_none() {
-                    width = Some(line.len())
-                } else {
-                    assert_eq!(
-                        width,
-                        Some(line.len()),
-                        "A line was the wrong length! Expected {:?}",
-                        width
-                    );
-                }
-
-                line.chars()
-                    .map(|c| match c {
-                        '.' => false,
-                        '#' => true,
-                        oh_no => panic!("the character {} was encountered!", oh_no),
-                    })
-                    .collect()
-            })
-            .collect();
-
-        Self {
-            trees,
-            width: width.unwrap(),
-        }
+    fn new(input: &str) -> Result<Self> {
+        Ok(Self {
+            trees: Grid::parse(input)?.with_edges(Edges::WrapX),
+        })
     }
 
     /// Is there a tree at the given coordinate?
     /// Return None if it is out-of-bounds to the south
-    fn tree_at(&self, x: usize, y: usize) -> Option<bool> {
-        let x = x % self.width;
-        self.trees.get(y).map(|row| row.get(x).copied()).flatten()
+    fn tree_at(&self, x: isize, y: usize) -> Option<bool> {
+        self.trees.get(x, y as isize).copied()
     }
 
-    /// Traverse the wilderness with the given dx and dy.
+    /// Traverse the wilderness down the given slope.
     /// Return the number of trees hit.
-    fn traverse(&self, dx: usize, dy: usize) -> usize {
-        let mut count = 0;
-        for step in 0.. {
-            let x = step * dx;
-            let y = step * dy;
-            match self.tree_at(x, y) {
-                Some(true) => count += 1,
-                Some(false) => {} // do nothing
-                None => break,
-            }
-        }
-        count
+    pub fn traverse(&self, slope: Slope) -> usize {
+        self.path(slope).trees_hit()
     }
 }
 
 #[test]
-fn part1() {
-    let forest = Forest::new(INPUT);
-    let count = forest.traverse(3, 1);
-    println!("Trees hit: {}", count);
+fn part1() -> Result<()> {
+    let forest = Day3::parse(&test_input!(3))?;
+    let answer = Day3::part1(&forest)?;
+    println!("Trees hit: {}", answer);
+    check_answer!(3, One, answer);
+
+    Ok(())
 }
 
 #[test]
-fn part2() {
-    let forest = Forest::new(INPUT);
-    let mega_count = [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)]
-        .iter()
-        .map(|&(dx, dy)| forest.traverse(dx, dy))
-        .fold_first(|acc, x| acc * x)
-        .unwrap();
-    println!("MEGA COUNT: {}", mega_count);
+fn part2() -> Result<()> {
+    let forest = Day3::parse(&test_input!(3))?;
+    let answer = Day3::part2(&forest)?;
+    println!("MEGA COUNT: {}", answer);
+    check_answer!(3, Two, answer);
+
+    Ok(())
+}
+
+#[test]
+fn example() -> Result<()> {
+    let forest = Day3::parse(
+        "..##.......
+#...#...#..
+.#....#..#.
+..#.#...#.#
+.#...##..#.
+..#.##.....
+.#.#.#....#
+.#........#
+#.##...#...
+#...##....#
+.#..#...#.#",
+    )?;
+    assert_eq!(Day3::part1(&forest)?, 7);
+    assert_eq!(Day3::part2(&forest)?, 336);
+
+    let ranked = forest.rank_slopes(3);
+    assert_eq!(ranked.len(), 7 * 3);
+    assert_eq!(ranked[0], (Slope::new(1, 3)?, 0));
+    assert_eq!(ranked.last(), Some(&(Slope::new(3, 1)?, 7)));
+
+    Ok(())
 }
@@ -0,0 +1,246 @@
+use std::{collections::HashMap, fmt::Write as _, fs, io::Write, path::Path as FsPath};
+
+use anyhow::{anyhow, Result};
+use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
+
+use super::{Forest, Path};
+
+/// What to draw in one square of a rendered path
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Cell {
+    Open,
+    Tree,
+    /// Landed on a tree
+    Hit,
+    /// Landed on an open square
+    Miss,
+}
+
+impl Cell {
+    /// The usual way of drawing these from the puzzle
+    fn to_char(self) -> char {
+        match self {
+            Cell::Open => '.',
+            Cell::Tree => '#',
+            Cell::Hit => 'X',
+            Cell::Miss => 'O',
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            Cell::Open => Color::White,
+            Cell::Tree => Color::Green,
+            Cell::Hit => Color::Red,
+            Cell::Miss => Color::Cyan,
+        }
+    }
+
+    fn svg_color(self) -> &'static str {
+        match self {
+            Cell::Open => "#f4f1e8",
+            Cell::Tree => "#2e7d32",
+            Cell::Hit => "#d32f2f",
+            Cell::Miss => "#29b6f6",
+        }
+    }
+}
+
+/// How many pixels wide and tall a square is in an SVG
+const SVG_CELL: usize = 10;
+
+/// The most copies of the forest that get drawn side by side.
+/// Steep enough slopes would need billions of them.
+const MAX_COPIES: isize = 64;
+
+impl Forest {
+    /// Lay out the forest with the path drawn on it, row by row.
+    ///
+    /// The forest is repeated left and right enough times to fit the whole path in,
+    /// always in whole copies of the input.
+    /// Errors if that would take more than `MAX_COPIES` copies.
+    fn path_cells(&self, path: &Path) -> Result<Vec<Vec<Cell>>> {
+        let width = self.trees.width() as isize;
+        let (min_x, max_x) = path.x_range();
+        // which copies the path starts and ends in
+        let (first, last) = (min_x.div_euclid(width), max_x.div_euclid(width));
+        let columns = (|| {
+            let copies = last.checked_sub(first)?.checked_add(1)?;
+            if copies > MAX_COPIES {
+                return None;
+            }
+            Some(first.checked_mul(width)?..last.checked_add(1)?.checked_mul(width)?)
+        })()
+        .ok_or_else(|| {
+            anyhow!(
+                "slope {} goes too far sideways to draw in {} copies of the forest",
+                path.slope,
+                MAX_COPIES
+            )
+        })?;
+
+        let landed = path
+            .steps
+            .iter()
+            .map(|step| ((step.x, step.y), step.tree))
+            .collect::<HashMap<_, _>>();
+        Ok((0..self.trees.height())
+            .map(|y| {
+                columns
+                    .clone()
+                    .map(|x| match (landed.get(&(x, y)), self.tree_at(x, y)) {
+                        (Some(true), _) => Cell::Hit,
+                        (Some(false), _) => Cell::Miss,
+                        (None, Some(true)) => Cell::Tree,
+                        _ => Cell::Open,
+                    })
+                    .collect()
+            })
+            .collect())
+    }
+
+    /// Draw the path as plain text, with `X` for trees hit and `O` for open squares landed on.
+    pub fn render_text(&self, path: &Path) -> Result<String> {
+        let mut out = String::new();
+        for row in self.path_cells(path)? {
+            out.extend(row.into_iter().map(Cell::to_char));
+            out.push('\n');
+        }
+        Ok(out)
+    }
+
+    /// Draw the path as an SVG picture.
+    pub fn render_svg(&self, path: &Path) -> Result<String> {
+        let cells = self.path_cells(path)?;
+        let width = cells.first().map_or(0, |row| row.len()) * SVG_CELL;
+        let height = cells.len() * SVG_CELL;
+
+        let mut out = String::new();
+        // writing to a string can't fail, so the unwraps are ok
+        writeln!(
+            out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#,
+            width, height
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "<title>slope {}: {} tree(s) hit</title>",
+            path.slope,
+            path.trees_hit()
+        )
+        .unwrap();
+        for (y, row) in cells.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                writeln!(
+                    out,
+                    r#"<rect x="{}" y="{}" width="{3}" height="{3}" fill="{}"/>"#,
+                    x * SVG_CELL,
+                    y * SVG_CELL,
+                    cell.svg_color(),
+                    SVG_CELL
+                )
+                .unwrap();
+            }
+        }
+        out.push_str("</svg>\n");
+        Ok(out)
+    }
+
+    /// Draw the path in color, like the text version.
+    pub fn write_colored(&self, path: &Path, out: &mut impl WriteColor) -> Result<()> {
+        let mut col = ColorSpec::new();
+        for row in self.path_cells(path)? {
+            for cell in row {
+                col.set_fg(Some(cell.color()))
+                    .set_bold(matches!(cell, Cell::Hit | Cell::Miss));
+                out.set_color(&col)?;
+                write!(out, "{}", cell.to_char())?;
+            }
+            out.reset()?;
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+
+    /// Print the path to the console, in color if it can.
+    ///
+    /// The runner doesn't draw paths, so this and `save_path` are only for using the library directly.
+    pub fn print_path(&self, path: &Path) -> Result<()> {
+        let mut stdout = StandardStream::stdout(ColorChoice::Auto);
+        self.write_colored(path, &mut stdout)
+    }
+
+    /// Save the path to a file, as an SVG if it ends in `.svg` and as text otherwise.
+    pub fn save_path(&self, path: &Path, file: impl AsRef<FsPath>) -> Result<()> {
+        let file = file.as_ref();
+        let is_svg = file
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("svg"));
+        let contents = if is_svg {
+            self.render_svg(path)?
+        } else {
+            self.render_text(path)?
+        };
+        fs::write(file, contents)?;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_render() -> Result<()> {
+    use super::Slope;
+    use termcolor::Buffer;
+
+    let forest = Forest::new("..#\n#.#\n.#.\n##.")?;
+
+    let path = forest.path("1".parse()?);
+    assert_eq!(
+        forest.render_text(&path)?,
+        "O.#..#\n#O##.#\n.#O.#.\n##.X#.\n"
+    );
+    // going left puts copies on the left
+    let path = forest.path("-2".parse()?);
+    assert_eq!(
+        forest.render_text(&path)?,
+        "..#..#O.#\n#.##O##.#\n.#O.#..#.\nX#.##.##.\n"
+    );
+
+    let svg = forest.render_svg(&path)?;
+    assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="90" height="40""#));
+    assert!(svg.contains("<title>slope -2/1: 1 tree(s) hit</title>"));
+    assert_eq!(svg.matches("<rect").count(), 9 * 4);
+    assert_eq!(svg.matches(Cell::Hit.svg_color()).count(), 1);
+    assert_eq!(svg.matches(Cell::Miss.svg_color()).count(), 3);
+
+    let mut colored = Buffer::no_color();
+    forest.write_colored(&path, &mut colored)?;
+    assert_eq!(
+        String::from_utf8(colored.into_inner())?,
+        forest.render_text(&path)?
+    );
+
+    // this would need a copy of the forest for every square up to isize::MAX
+    let path = forest.path(Slope::new(isize::MAX, 1)?);
+    assert_eq!(
+        forest.render_text(&path).unwrap_err().to_string(),
+        format!(
+            "slope {}/1 goes too far sideways to draw in 64 copies of the forest",
+            isize::MAX
+        )
+    );
+    assert!(forest
+        .render_svg(&forest.path(Slope::new(isize::MIN, 1)?))
+        .is_err());
+    // but there's room for a fair way over
+    assert_eq!(
+        forest
+            .render_text(&forest.path("40".parse()?))?
+            .lines()
+            .next()
+            .map(str::len),
+        Some(3 * 41)
+    );
+
+    Ok(())
+}
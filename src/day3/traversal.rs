@@ -0,0 +1,215 @@
+use std::{
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+
+use super::Forest;
+
+/// Which way to go down the hill: `right` across for every `down` rows.
+///
+/// `right` can be negative to go left. The steps aren't reduced,
+/// so 2/2 only lands on every other row where 1/1 lands on all of them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct Slope {
+    right: isize,
+    down: usize,
+}
+
+impl Slope {
+    /// Errors if `down` is 0, because then you'd never get to the bottom.
+    pub fn new(right: isize, down: usize) -> Result<Self> {
+        if down == 0 {
+            bail!("a slope has to go down");
+        }
+        Ok(Self { right, down })
+    }
+
+    pub fn right(&self) -> isize {
+        self.right
+    }
+
+    pub fn down(&self) -> usize {
+        self.down
+    }
+}
+
+impl Display for Slope {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.right, self.down)
+    }
+}
+
+impl FromStr for Slope {
+    type Err = anyhow::Error;
+    /// Parse a slope like `3/1` or `-1/2`, or just `3` for going down 1 at a time.
+    fn from_str(s: &str) -> Result<Self> {
+        let (right, down) = match s.split_once('/') {
+            Some((right, down)) => (right, down),
+            None => (s, "1"),
+        };
+        let right = right
+            .trim()
+            .parse()
+            .with_context(|| anyhow!("bad rightwards step in `{}`", s))?;
+        let down = down
+            .trim()
+            .parse()
+            .with_context(|| anyhow!("bad downwards step in `{}`", s))?;
+        Self::new(right, down)
+    }
+}
+
+/// One square landed on.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Step {
+    /// How far across from the start this is, without wrapping,
+    /// so it can be off either side of the input.
+    pub x: isize,
+    pub y: usize,
+    pub tree: bool,
+}
+
+/// Every square landed on going down a slope, from the top to the bottom.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Path {
+    pub slope: Slope,
+    pub steps: Vec<Step>,
+}
+
+impl Path {
+    /// The steps that hit a tree
+    pub fn hits(&self) -> impl Iterator<Item = &Step> {
+        self.steps.iter().filter(|step| step.tree)
+    }
+
+    pub fn trees_hit(&self) -> usize {
+        self.hits().count()
+    }
+
+    /// How far left and right the path goes, without wrapping
+    pub fn x_range(&self) -> (isize, isize) {
+        let xs = self.steps.iter().map(|step| step.x);
+        (xs.clone().min().unwrap_or(0), xs.max().unwrap_or(0))
+    }
+}
+
+impl Forest {
+    /// Go down a slope from the top left, recording everywhere we land.
+    pub fn path(&self, slope: Slope) -> Path {
+        // a huge slope can overflow before it falls off the bottom, so stop there too
+        let steps = (0..)
+            .map_while(|step: usize| {
+                let x = (step as isize).checked_mul(slope.right)?;
+                let y = step.checked_mul(slope.down)?;
+                Some((x, y))
+            })
+            .map_while(|(x, y)| self.tree_at(x, y).map(|tree| Step { x, y, tree }))
+            .collect();
+        Path { slope, steps }
+    }
+
+    /// Try every slope going at most `bound` squares right, left and down per step,
+    /// and rank them by how many trees they hit, fewest first.
+    /// Ties are broken by the slope, so the output is always in the same order.
+    ///
+    /// Slopes that aren't in lowest terms take different paths (see [`Slope`]),
+    /// so 2/2 gets ranked as well as 1/1.
+    pub fn rank_slopes(&self, bound: usize) -> Vec<(Slope, usize)> {
+        let bound_signed = bound as isize;
+        let mut ranked = (1..=bound)
+            .flat_map(|down| (-bound_signed..=bound_signed).map(move |right| Slope { right, down }))
+            .map(|slope| (slope, self.traverse(slope)))
+            .collect::<Vec<_>>();
+        ranked.sort_by_key(|&(slope, hits)| (hits, slope));
+        ranked
+    }
+}
+
+#[test]
+fn test_paths() -> Result<()> {
+    let forest = Forest::new("..#\n#.#\n.#.\n##.")?;
+
+    let path = forest.path("1/1".parse()?);
+    assert_eq!(
+        path.steps,
+        vec![
+            Step {
+                x: 0,
+                y: 0,
+                tree: false
+            },
+            Step {
+                x: 1,
+                y: 1,
+                tree: false
+            },
+            Step {
+                x: 2,
+                y: 2,
+                tree: false
+            },
+            Step {
+                x: 3,
+                y: 3,
+                tree: true
+            },
+        ]
+    );
+    assert_eq!(path.trees_hit(), 1);
+    assert_eq!(path.x_range(), (0, 3));
+
+    // going left wraps round the other way
+    let path = forest.path("-1".parse()?);
+    assert_eq!(
+        path.hits().map(|step| (step.x, step.y)).collect::<Vec<_>>(),
+        vec![(-1, 1), (-2, 2), (-3, 3)]
+    );
+    assert_eq!(path.x_range(), (-3, 0));
+
+    let path = forest.path(Slope::new(1, 2)?);
+    assert_eq!(
+        path.steps
+            .iter()
+            .map(|step| (step.x, step.y))
+            .collect::<Vec<_>>(),
+        vec![(0, 0), (1, 2)]
+    );
+
+    let ranked = forest.rank_slopes(1);
+    assert_eq!(
+        ranked,
+        vec![
+            (Slope::new(1, 1)?, 1),
+            (Slope::new(0, 1)?, 2),
+            (Slope::new(-1, 1)?, 3),
+        ]
+    );
+
+    // 2/2 skips the tree 1/1 lands on at the bottom
+    let ranked = forest.rank_slopes(2);
+    assert_eq!(ranked.len(), 5 * 2);
+    let hits = |right, down| {
+        let slope = Slope::new(right, down).unwrap();
+        ranked
+            .iter()
+            .find(|(it, _)| *it == slope)
+            .map(|&(_, hits)| hits)
+    };
+    assert_eq!(hits(2, 2), Some(0));
+    assert_eq!(hits(1, 1), Some(1));
+
+    // this used to overflow on the second step
+    let path = forest.path(format!("{}/1", isize::MAX).parse()?);
+    assert_eq!(path.steps.len(), 2);
+    let path = forest.path(Slope::new(1, usize::MAX)?);
+    assert_eq!(path.steps.len(), 1);
+
+    assert!("1/0".parse::<Slope>().is_err());
+    assert!("1/-1".parse::<Slope>().is_err());
+    assert!("right".parse::<Slope>().is_err());
+    assert_eq!("-3 / 2".parse::<Slope>()?.to_string(), "-3/2");
+
+    Ok(())
+}
@@ -1,25 +1,111 @@
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 
+use crate::{
+    input::ExtraFiles,
+    schema::{Document, Schema},
+    solution::Solution,
+};
+
+mod output;
 mod parsing;
+mod typed;
 mod validation;
 
-const INPUT: &str = include_str!("input.txt");
-
-#[derive(Debug, Default)]
-struct Passport {
-    birth_year: Option<String>,
-    issue_year: Option<String>,
-    expiration_year: Option<String>,
-    height: Option<String>,
-    /// Hex color
-    hair_color: Option<String>,
-    eye_color: Option<String>,
-    passport_id: Option<String>,
-    country_id: Option<String>,
+pub use output::{to_csv, to_json, write_batch, Normalize};
+pub use typed::{EyeColor, HairColor, HeightUnit, PassportId, ValidPassport};
+
+pub use validation::{batch_report, load_schema, DEFAULT_SCHEMA, SCHEMA_FILE};
+
+pub struct Day4;
+
+impl Solution for Day4 {
+    /// The schema to check against, and the passports
+    type Parsed = (Schema, Vec<Passport>);
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    const EXTRA_FILES: &'static [&'static str] = &[SCHEMA_FILE];
+
+    /// Parse the passports and check them against the default schema
+    fn parse(input: &str) -> Result<Self::Parsed> {
+        Self::parse_with(input, &ExtraFiles::default())
+    }
+
+    /// Parse the passports, and the schema next to them if there was one
+    fn parse_with(input: &str, extra: &ExtraFiles) -> Result<Self::Parsed> {
+        let schema = match extra.get(SCHEMA_FILE) {
+            Some(schema) => schema
+                .parse()
+                .with_context(|| format!("in {}", SCHEMA_FILE))?,
+            None => DEFAULT_SCHEMA.clone(),
+        };
+        Ok((schema, Passport::parse_all(input)?))
+    }
+
+    fn part1((schema, passports): &Self::Parsed) -> Result<usize> {
+        Ok(passports
+            .iter()
+            .filter(|pp| pp.validate_part1(schema))
+            .count())
+    }
+
+    fn part2((schema, passports): &Self::Parsed) -> Result<usize> {
+        Ok(passports
+            .iter()
+            .filter(|pp| pp.validate_part2(schema))
+            .count())
+    }
+}
+
+/// A passport, with whatever fields it came with.
+/// What fields it's supposed to have is up to the schema checking it.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct Passport {
+    /// `(key, value)`, in the order they were in the input
+    fields: Vec<(String, String)>,
+}
+
+impl Passport {
+    /// Get the value of a field, if it's there
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// All the fields, in the order they were in the input
+    pub fn fields(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.fields.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// Add a field. Errors if it's already there.
+    fn insert(&mut self, key: String, value: String) -> Result<()> {
+        if let Some(old) = self.get(&key) {
+            bail!(
+                "Field `{}` was already `{}`, so it can't be `{}`!",
+                key,
+                old,
+                value
+            );
+        }
+        self.fields.push((key, value));
+        Ok(())
+    }
+}
+
+impl Document for Passport {
+    fn get(&self, key: &str) -> Option<&str> {
+        Passport::get(self, key)
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.fields.iter().map(|(k, _)| k.as_str()).collect()
+    }
 }
 
-#[derive(Debug)]
-enum Height {
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Height {
     Inches(u16),
     Centimeters(u16),
 }
@@ -39,35 +125,45 @@ enum Height {
 
 #[test]
 fn part1() -> Result<()> {
-    let passports = Passport::parse_all(INPUT)?;
-
-    // Count how many have Some for all *except* cid
-    let valid_count = passports
-        .iter()
-        .filter(|pp| {
-            [
-                &pp.birth_year,
-                &pp.expiration_year,
-                &pp.eye_color,
-                &pp.hair_color,
-                &pp.height,
-                &pp.issue_year,
-                &pp.passport_id,
-            ]
-            .iter()
-            .all(|field| field.is_some())
-        })
-        .count();
-    println!("valid count: {}", valid_count);
+    let passports = Day4::parse(&test_input!(4))?;
+    let answer = Day4::part1(&passports)?;
+    println!("valid count: {}", answer);
+    check_answer!(4, One, answer);
 
     Ok(())
 }
 
 #[test]
 fn part2() -> Result<()> {
-    let passports = Passport::parse_all(INPUT)?;
-    let valid_count = passports.iter().filter(|pp| pp.validate_part2()).count();
-    println!("valid count: {}", valid_count);
+    let passports = Day4::parse(&test_input!(4))?;
+    let answer = Day4::part2(&passports)?;
+    println!("valid count: {}", answer);
+    check_answer!(4, Two, answer);
+
+    Ok(())
+}
+
+#[test]
+fn part1_test() -> Result<()> {
+    let passports = Day4::parse(
+        "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd
+byr:1937 iyr:2017 cid:147 hgt:183cm
+
+iyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884
+hcl:#cfa07d byr:1929
+
+hcl:#ae17e1 iyr:2013
+eyr:2024
+ecl:brn pid:760753108 byr:1931
+hgt:179cm
+
+hcl:#cfa07d eyr:2025 pid:166559648
+iyr:2011 ecl:brn hgt:59in
+",
+    )?;
+    assert_eq!(passports.1.len(), 4);
+    assert_eq!(Day4::part1(&passports)?, 2);
+    assert_eq!(passports.1[1].get("pid"), Some("028048884"));
 
     Ok(())
 }
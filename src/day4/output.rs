@@ -0,0 +1,253 @@
+use std::fmt::{Display, Formatter, Write as _};
+
+use super::{parsing::parse_height, validation::DEFAULT_SCHEMA, HeightUnit, Passport, PassportId};
+
+/// Ways to tidy up a passport's fields before writing it out.
+///
+/// Anything that can't be tidied up (say, a height with no units) is left as it was,
+/// so writing out an invalid passport doesn't lose anything.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct Normalize {
+    /// Lowercase hair and eye colors
+    pub lowercase_colors: bool,
+    /// Put leading zeroes on passport IDs that are too short
+    pub pad_pid: bool,
+    /// Convert heights to this unit
+    pub height_unit: Option<HeightUnit>,
+}
+
+impl Normalize {
+    /// Do all of it, with heights in the given unit
+    pub fn all(height_unit: HeightUnit) -> Self {
+        Self {
+            lowercase_colors: true,
+            pad_pid: true,
+            height_unit: Some(height_unit),
+        }
+    }
+
+    fn field(&self, key: &str, value: &str) -> String {
+        match key {
+            "hcl" | "ecl" if self.lowercase_colors => value.to_ascii_lowercase(),
+            "pid"
+                if self.pad_pid
+                    && !value.is_empty()
+                    && value.len() < PassportId::DIGITS
+                    && value.chars().all(|c| c.is_ascii_digit()) =>
+            {
+                format!("{:0>width$}", value, width = PassportId::DIGITS)
+            }
+            // anything too tall to convert gets left alone instead of wrapping round
+            "hgt" => match (self.height_unit, parse_height(value)) {
+                (Some(unit), Ok((height, ""))) => height
+                    .to_unit(unit)
+                    .map_or_else(|_| value.to_string(), |height| height.to_string()),
+                _ => value.to_string(),
+            },
+            _ => value.to_string(),
+        }
+    }
+}
+
+impl Passport {
+    /// The fields in the order they get written out in:
+    /// the ones the default schema knows about in its order, then any others alphabetically.
+    pub fn sorted_fields(&self) -> Vec<(&str, &str)> {
+        let mut fields = self.fields().collect::<Vec<_>>();
+        fields.sort_by_key(|&(key, _)| field_order(key));
+        fields
+    }
+
+    /// Copy this passport with its fields tidied up
+    pub fn normalized(&self, how: &Normalize) -> Passport {
+        Passport {
+            fields: self
+                .fields()
+                .map(|(key, value)| (key.to_string(), how.field(key, value)))
+                .collect(),
+        }
+    }
+}
+
+/// Sort key for fields, so they always come out in the same order
+fn field_order(key: &str) -> (usize, &str) {
+    let known = DEFAULT_SCHEMA.fields();
+    let idx = known
+        .iter()
+        .position(|rule| rule.name == key)
+        .unwrap_or(known.len());
+    (idx, key)
+}
+
+impl Display for Passport {
+    /// Write it out as `key:value` pairs on one line, in the order from `sorted_fields`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (idx, (key, value)) in self.sorted_fields().into_iter().enumerate() {
+            if idx != 0 {
+                f.write_char(' ')?;
+            }
+            write!(f, "{}:{}", key, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Write out passports in the same format they're read in,
+/// one per line with blank lines between them.
+pub fn write_batch(passports: &[Passport]) -> String {
+    let mut out = String::new();
+    for (idx, pp) in passports.iter().enumerate() {
+        if idx != 0 {
+            out.push('\n');
+        }
+        // writing to a string can't fail
+        writeln!(out, "{}", pp).unwrap();
+    }
+    out
+}
+
+/// Write out passports as a JSON array of objects, with the fields in a stable order.
+pub fn to_json(passports: &[Passport]) -> String {
+    let mut out = String::from("[");
+    for (idx, pp) in passports.iter().enumerate() {
+        out.push_str(if idx == 0 { "\n  {" } else { ",\n  {" });
+        for (idx, (key, value)) in pp.sorted_fields().into_iter().enumerate() {
+            if idx != 0 {
+                out.push_str(", ");
+            }
+            write_json_string(&mut out, key);
+            out.push_str(": ");
+            write_json_string(&mut out, value);
+        }
+        out.push('}');
+    }
+    if !passports.is_empty() {
+        out.push('\n');
+    }
+    out.push_str("]\n");
+    out
+}
+
+fn write_json_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Write out passports as CSV, with a header row.
+///
+/// There's a column for every field any of the passports has, in the same order as
+/// `sorted_fields`; passports missing a field get an empty cell.
+pub fn to_csv(passports: &[Passport]) -> String {
+    let mut columns = passports
+        .iter()
+        .flat_map(|pp| pp.fields().map(|(key, _)| key))
+        .collect::<Vec<_>>();
+    columns.sort_by_key(|key| field_order(key));
+    columns.dedup();
+
+    let mut out = String::new();
+    write_csv_row(&mut out, columns.iter().copied());
+    for pp in passports {
+        write_csv_row(
+            &mut out,
+            columns.iter().map(|key| pp.get(key).unwrap_or("")),
+        );
+    }
+    out
+}
+
+fn write_csv_row<'a>(out: &mut String, cells: impl Iterator<Item = &'a str>) {
+    for (idx, cell) in cells.enumerate() {
+        if idx != 0 {
+            out.push(',');
+        }
+        if cell.contains([',', '"', '\n', '\r']) {
+            write!(out, "\"{}\"", cell.replace('"', "\"\"")).unwrap();
+        } else {
+            out.push_str(cell);
+        }
+    }
+    out.push('\n');
+}
+
+#[test]
+fn test_output() -> anyhow::Result<()> {
+    let passports = Passport::parse_all(
+        "hcl:#FFFFFD pid:8600333 zzz:1
+ecl:GRY byr:1937 hgt:72in
+
+hgt:190 iyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884
+",
+    )?;
+
+    let batch = write_batch(&passports);
+    assert_eq!(
+        batch,
+        "byr:1937 hgt:72in hcl:#FFFFFD ecl:GRY pid:8600333 zzz:1
+
+iyr:2013 eyr:2023 hgt:190 ecl:amb pid:028048884 cid:350
+"
+    );
+    // round trips
+    assert_eq!(write_batch(&Passport::parse_all(&batch)?), batch);
+
+    let tidy = Normalize::all(HeightUnit::Centimeters);
+    let normalized = passports
+        .iter()
+        .map(|pp| pp.normalized(&tidy))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        normalized[0].to_string(),
+        "byr:1937 hgt:183cm hcl:#fffffd ecl:gry pid:008600333 zzz:1"
+    );
+    // no units, so it's left alone
+    assert_eq!(normalized[1].get("hgt"), Some("190"));
+    assert_eq!(
+        passports[0]
+            .normalized(&Normalize {
+                height_unit: Some(HeightUnit::Inches),
+                ..Normalize::default()
+            })
+            .to_string(),
+        passports[0].to_string()
+    );
+
+    let mut tall = Passport::default();
+    tall.insert("hgt".to_string(), "30000in".to_string())?;
+    assert_eq!(tall.normalized(&tidy).get("hgt"), Some("30000in"));
+
+    assert_eq!(
+        to_json(&normalized[..1]),
+        r##"[
+  {"byr": "1937", "hgt": "183cm", "hcl": "#fffffd", "ecl": "gry", "pid": "008600333", "zzz": "1"}
+]
+"##
+    );
+    assert_eq!(to_json(&[]), "[]\n");
+
+    assert_eq!(
+        to_csv(&normalized),
+        "byr,iyr,eyr,hgt,hcl,ecl,pid,cid,zzz
+1937,,,183cm,#fffffd,gry,008600333,,1
+,2013,2023,190,,amb,028048884,350,
+"
+    );
+
+    let mut weird = Passport::default();
+    weird.insert("a\"b".to_string(), "1,2".to_string())?;
+    assert_eq!(to_json(&[weird.clone()]), "[\n  {\"a\\\"b\": \"1,2\"}\n]\n");
+    assert_eq!(to_csv(&[weird]), "\"a\"\"b\"\n\"1,2\"\n");
+
+    Ok(())
+}
@@ -1,93 +1,41 @@
 use anyhow::{anyhow, bail, Result};
 
+use crate::records::{self, Record, RecordError};
+
 use super::{Height, Passport};
 
 impl Passport {
-    /// Parse out a Passport entry from a string.
-    ///
-    /// Returns either the passport and the remainder of the string it did not parse,
-    /// or an error.
-    pub fn parse(i: &str) -> Result<(Self, &str)> {
-        let mut i = i;
-        // default = all is NONE
+    /// Make a passport out of a record of its fields.
+    pub fn from_record(record: &Record<'_>) -> Result<Self, RecordError> {
         let mut out = Passport::default();
-
-        while !i.is_empty() && i.find(|c: char| !c.is_whitespace()).is_some() {
-            // find the key
-            let (key, new_i) = i.split_at(3);
-            // Expect a colon
-            let (this_better_be_a_colon, new_i) = new_i.split_at(1);
-            if this_better_be_a_colon != ":" {
-                bail!("there was no colon");
-            }
-            i = new_i;
-            // do the field
-            let ws_idx = i
-                .find(char::is_whitespace)
-                .ok_or_else(|| anyhow!("End of file reached from parse_int"))?;
-            let (value, new_i) = i.split_at(ws_idx);
-            i = new_i;
-            let value = value.to_string();
-            // match out the key
-            let out_field = match key {
-                "byr" => &mut out.birth_year,
-                "iyr" => &mut out.issue_year,
-                "eyr" => &mut out.expiration_year,
-                "hgt" => &mut out.height,
-                "hcl" => &mut out.hair_color,
-                "ecl" => &mut out.eye_color,
-                "pid" => &mut out.passport_id,
-                "cid" => &mut out.country_id,
-                oh_no => bail!("Unknown key `{}`", oh_no),
-            };
-            if out_field.is_some() {
-                bail!("Field `{}` was already {:?}!", value, out_field)
-            } else {
-                *out_field = Some(value);
-            }
-
-            // Remove whitespace
-            if i.starts_with(char::is_whitespace) {
-                let content_idx = match i.find(|c: char| !c.is_whitespace()) {
-                    Some(it) => it,
-                    None => {
-                        // out of content! aka eof
-                        // overwrite i to be empty
-                        // this is a hack but ehh
-                        i = "";
-                        break;
-                    }
-                };
-                i = i.get(content_idx..).unwrap();
-                if content_idx >= 2 {
-                    // this means there were two lines of whitespace
-                    // or we're out of input
-                    break;
-                }
-            }
+        for field in &record.fields {
+            out.insert(field.key.to_string(), field.value.to_string())
+                .map_err(|e| RecordError::at(field, e.to_string()))?;
         }
+        Ok(out)
+    }
 
-        Ok((out, i))
+    /// Parse one passport from a string.
+    pub fn parse(i: &str) -> Result<Self> {
+        match Passport::parse_all(i)?.as_mut_slice() {
+            [pp] => Ok(std::mem::take(pp)),
+            oh_no => bail!("expected 1 passport but there were {}", oh_no.len()),
+        }
     }
 
-    /// Parse ALL the passports from a string
+    /// Parse ALL the passports from a string, separated by blank lines
     pub fn parse_all(i: &str) -> Result<Vec<Self>> {
-        let mut i = i;
-        let mut out = Vec::new();
-        while !i.is_empty() {
-            let (pp, new_i) = Passport::parse(i)?;
-            i = new_i;
-            out.push(pp);
-        }
-        Ok(out)
+        let passports = records::records(i)?
+            .iter()
+            .map(Passport::from_record)
+            .collect::<Result<_, _>>()?;
+        Ok(passports)
     }
 }
 
-/// parse either base 10 or base 16 with #
-fn parse_int(i: &str) -> Result<(u64, &str)> {
-    let ws_idx = i
-        .find(char::is_whitespace)
-        .ok_or_else(|| anyhow!("End of file reached from parse_int"))?;
+/// parse either base 10 or base 16 with #, up to the next whitespace or the end
+pub(super) fn parse_int(i: &str) -> Result<(u64, &str)> {
+    let ws_idx = i.find(char::is_whitespace).unwrap_or(i.len());
     let (num_str, i) = i.split_at(ws_idx);
     if num_str.starts_with('#') {
         // base 16 time!
@@ -100,14 +48,17 @@ fn parse_int(i: &str) -> Result<(u64, &str)> {
     }
 }
 
-fn parse_height(i: &str) -> Result<(Height, &str)> {
+pub(super) fn parse_height(i: &str) -> Result<(Height, &str)> {
     let units_idx = i
         .find(|c: char| !c.is_numeric())
         .ok_or_else(|| anyhow!("Units not found in `parse_height`"))?;
     let (num_str, i) = i.split_at(units_idx);
     let num = num_str.parse()?;
     // Find the discriminator
-    let (units, i) = i.split_at(2);
+    let units = i
+        .get(..2)
+        .ok_or_else(|| anyhow!("Units `{}` are too short", i))?;
+    let i = &i[2..];
     let height = match units {
         "in" => Height::Inches,
         "cm" => Height::Centimeters,
@@ -115,3 +66,27 @@ fn parse_height(i: &str) -> Result<(Height, &str)> {
     }(num);
     Ok((height, i))
 }
+
+#[test]
+fn test_parse() -> Result<()> {
+    // crlf, trailing whitespace and no newline at the end
+    let passports =
+        Passport::parse_all("ecl:gry pid:860033327 \r\nbyr:1937\r\n\r\n\r\nhcl:#cfa07d")?;
+    assert_eq!(passports.len(), 2);
+    assert_eq!(passports[0].get("byr"), Some("1937"));
+    assert_eq!(passports[1].get("hcl"), Some("#cfa07d"));
+
+    let err = Passport::parse_all("ecl:gry\n\nhcl:#cfa07d\n  hcl:#fffffd").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "line 4, column 3: Field `hcl` was already `#cfa07d`, so it can't be `#fffffd`!"
+    );
+    // short keys used to panic
+    let err = Passport::parse("ab").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "line 1, column 1: expected `key:value` but found `ab`"
+    );
+
+    Ok(())
+}
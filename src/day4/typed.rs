@@ -0,0 +1,330 @@
+use std::{
+    convert::{TryFrom, TryInto},
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+
+use anyhow::{anyhow, bail, Context, Error, Result};
+
+use crate::schema::Schema;
+
+use super::{
+    parsing::{parse_height, parse_int},
+    validation::DEFAULT_SCHEMA,
+    Height, Passport,
+};
+
+impl Display for Height {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Height::Inches(it) => write!(f, "{}in", it),
+            Height::Centimeters(it) => write!(f, "{}cm", it),
+        }
+    }
+}
+
+impl Height {
+    /// Convert to centimeters, rounding to the nearest one
+    pub fn centimeters(&self) -> u32 {
+        match *self {
+            Height::Centimeters(it) => it as u32,
+            // 2.54 cm to the inch
+            Height::Inches(it) => (it as u32 * 254 + 50) / 100,
+        }
+    }
+
+    /// Convert to inches, rounding to the nearest one
+    pub fn inches(&self) -> u32 {
+        match *self {
+            Height::Inches(it) => it as u32,
+            Height::Centimeters(it) => (it as u32 * 100 + 127) / 254,
+        }
+    }
+
+    pub fn unit(&self) -> HeightUnit {
+        match self {
+            Height::Inches(_) => HeightUnit::Inches,
+            Height::Centimeters(_) => HeightUnit::Centimeters,
+        }
+    }
+
+    /// Convert to the given unit, rounding to the nearest one.
+    /// Errors if it's too tall to fit.
+    pub fn to_unit(self, unit: HeightUnit) -> Result<Height> {
+        let (amount, make): (_, fn(u16) -> Height) = match unit {
+            HeightUnit::Inches => (self.inches(), Height::Inches),
+            HeightUnit::Centimeters => (self.centimeters(), Height::Centimeters),
+        };
+        let amount = u16::try_from(amount)
+            .with_context(|| format!("{} is too tall to convert to {:?}", self, unit))?;
+        Ok(make(amount))
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum HeightUnit {
+    Inches,
+    Centimeters,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct HairColor {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Display for HairColor {
+    /// Writes it as `#rrggbb`
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+impl FromStr for HairColor {
+    type Err = Error;
+    /// Parse `#rrggbb`
+    fn from_str(s: &str) -> Result<Self> {
+        // from_str_radix lets a + through, so check all the digits first
+        if !s.starts_with('#') || s.len() != 7 || !s[1..].chars().all(|c| c.is_ascii_hexdigit()) {
+            bail!("`{}` isn't a color like `#rrggbb`", s);
+        }
+        let (rgb, rest) = parse_int(s)?;
+        if !rest.is_empty() {
+            bail!("`{}` isn't a color like `#rrggbb`", s);
+        }
+        Ok(Self {
+            r: (rgb >> 16) as u8,
+            g: (rgb >> 8) as u8,
+            b: rgb as u8,
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum EyeColor {
+    Amber,
+    Blue,
+    Brown,
+    Gray,
+    Green,
+    Hazel,
+    Other,
+}
+
+impl EyeColor {
+    /// The three-letter code for it
+    pub fn code(&self) -> &'static str {
+        match self {
+            EyeColor::Amber => "amb",
+            EyeColor::Blue => "blu",
+            EyeColor::Brown => "brn",
+            EyeColor::Gray => "gry",
+            EyeColor::Green => "grn",
+            EyeColor::Hazel => "hzl",
+            EyeColor::Other => "oth",
+        }
+    }
+}
+
+impl Display for EyeColor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+impl FromStr for EyeColor {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "amb" => EyeColor::Amber,
+            "blu" => EyeColor::Blue,
+            "brn" => EyeColor::Brown,
+            "gry" => EyeColor::Gray,
+            "grn" => EyeColor::Green,
+            "hzl" => EyeColor::Hazel,
+            "oth" => EyeColor::Other,
+            oh_no => bail!("unknown eye color `{}`", oh_no),
+        })
+    }
+}
+
+/// A passport ID, which is always 9 digits, leading zeroes and all
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct PassportId(u32);
+
+impl PassportId {
+    pub const DIGITS: usize = 9;
+
+    pub fn value(&self) -> u32 {
+        self.0
+    }
+}
+
+impl Display for PassportId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:0width$}", self.0, width = Self::DIGITS)
+    }
+}
+
+impl FromStr for PassportId {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        if s.len() != Self::DIGITS || !s.chars().all(|c| c.is_ascii_digit()) {
+            bail!("`{}` isn't {} digits", s, Self::DIGITS);
+        }
+        // 9 digits always fits
+        Ok(Self(s.parse()?))
+    }
+}
+
+/// A passport that's passed part 2's rules, with everything parsed out.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ValidPassport {
+    birth_year: u16,
+    issue_year: u16,
+    expiration_year: u16,
+    height: Height,
+    hair_color: HairColor,
+    eye_color: EyeColor,
+    passport_id: PassportId,
+    country_id: Option<String>,
+}
+
+impl ValidPassport {
+    pub fn birth_year(&self) -> u16 {
+        self.birth_year
+    }
+
+    pub fn issue_year(&self) -> u16 {
+        self.issue_year
+    }
+
+    pub fn expiration_year(&self) -> u16 {
+        self.expiration_year
+    }
+
+    pub fn height(&self) -> Height {
+        self.height
+    }
+
+    pub fn hair_color(&self) -> HairColor {
+        self.hair_color
+    }
+
+    pub fn eye_color(&self) -> EyeColor {
+        self.eye_color
+    }
+
+    pub fn passport_id(&self) -> PassportId {
+        self.passport_id
+    }
+
+    pub fn country_id(&self) -> Option<&str> {
+        self.country_id.as_deref()
+    }
+
+    /// Check the passport against a schema, and then parse everything.
+    /// Errors with everything wrong with it if it doesn't pass.
+    pub fn new(pp: &Passport, schema: &Schema) -> Result<Self> {
+        let report = pp.report(schema);
+        if !report.is_valid() {
+            bail!("{}", report);
+        }
+
+        // the schema should say they're all there
+        let field = |key: &str| pp.get(key).ok_or_else(|| anyhow!("{}: missing", key));
+        let year = |key: &str| -> Result<u16> {
+            let (year, _) = parse_int(field(key)?)?;
+            year.try_into()
+                .with_context(|| format!("{}: {} is too big", key, year))
+        };
+        let (height, _) = parse_height(field("hgt")?).context("hgt")?;
+
+        Ok(Self {
+            birth_year: year("byr")?,
+            issue_year: year("iyr")?,
+            expiration_year: year("eyr")?,
+            height,
+            hair_color: field("hcl")?.parse().context("hcl")?,
+            eye_color: field("ecl")?.parse().context("ecl")?,
+            passport_id: field("pid")?.parse().context("pid")?,
+            country_id: pp.get("cid").map(String::from),
+        })
+    }
+}
+
+impl TryFrom<&Passport> for ValidPassport {
+    type Error = Error;
+    /// Check the passport against the default part 2 schema, and then parse everything.
+    fn try_from(pp: &Passport) -> Result<Self> {
+        Self::new(pp, &DEFAULT_SCHEMA)
+    }
+}
+
+impl TryFrom<Passport> for ValidPassport {
+    type Error = Error;
+    fn try_from(pp: Passport) -> Result<Self> {
+        Self::try_from(&pp)
+    }
+}
+
+#[test]
+fn test_typed() -> Result<()> {
+    let passports = Passport::parse_all(
+        "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980
+hcl:#623a2f
+
+eyr:2029 ecl:blu cid:129 byr:1989
+iyr:2014 pid:896056539 hcl:#a97842 hgt:165cm
+
+hcl:dab227 iyr:2012
+ecl:brn hgt:182cm pid:021572410 eyr:2020 byr:1992 cid:277
+",
+    )?;
+
+    let valid = ValidPassport::try_from(&passports[0])?;
+    assert_eq!(valid.birth_year(), 1980);
+    assert_eq!(valid.height(), Height::Inches(74));
+    assert_eq!(valid.height().centimeters(), 188);
+    assert_eq!(
+        valid.height().to_unit(HeightUnit::Centimeters)?.inches(),
+        74
+    );
+    assert_eq!(
+        valid.hair_color(),
+        HairColor {
+            r: 0x62,
+            g: 0x3a,
+            b: 0x2f
+        }
+    );
+    assert_eq!(valid.eye_color(), EyeColor::Green);
+    assert_eq!(valid.passport_id().value(), 87499704);
+    assert_eq!(valid.passport_id().to_string(), "087499704");
+    assert_eq!(valid.country_id(), None);
+
+    let valid = ValidPassport::try_from(passports[1].clone())?;
+    assert_eq!(valid.height().to_string(), "165cm");
+    assert_eq!(valid.hair_color().to_string(), "#a97842");
+    assert_eq!(valid.country_id(), Some("129"));
+
+    let err = ValidPassport::try_from(&passports[2]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "hcl: `dab227` doesn't match `^#[0-9a-f]{6}$`"
+    );
+
+    assert!("#12345".parse::<HairColor>().is_err());
+    assert!("#1234567".parse::<HairColor>().is_err());
+    assert!("#+abcde".parse::<HairColor>().is_err());
+    assert_eq!(Height::Inches(30000).centimeters(), 76200);
+    assert!(Height::Inches(30000)
+        .to_unit(HeightUnit::Centimeters)
+        .is_err());
+    assert!("zzz".parse::<EyeColor>().is_err());
+    assert!("12345678".parse::<PassportId>().is_err());
+    assert!(parse_height("170c").is_err());
+
+    Ok(())
+}
@@ -1,82 +1,61 @@
+use once_cell::sync::Lazy;
+
 use super::Passport;
+use crate::{
+    input::InputLoader,
+    schema::{BatchReport, Report, Schema},
+};
 use anyhow::Result;
 
-impl Passport {
-    /// Validate this passport as per part 2.
-    /// returns `true` if it's valid, `false` otherwise
-    pub fn validate_part2(&self) -> bool {
-        macro_rules! unwrap_to_bool {
-            ( $( $field:ident ),* ) => {
-                $(
-                    let $field = match self.$field {
-                        Some(ref it) => it,
-                        None => return false,
-                    };
-                )*
-            }
-        }
-        unwrap_to_bool!(
-            birth_year,
-            issue_year,
-            expiration_year,
-            height,
-            hair_color,
-            eye_color,
-            passport_id
-        );
-
-        validate_year(&birth_year, 1920, 2002)
-            && validate_year(&issue_year, 2010, 2020)
-            && validate_year(&expiration_year, 2020, 2030)
-            && validate_height(&height)
-            && validate_hair(&hair_color)
-            && validate_eye(&eye_color)
-            && validate_pid(&passport_id)
+/// What the part 2 schema is called when it's next to the input,
+/// as `dayN/passport.schema` or `day4.passport.schema`.
+pub const SCHEMA_FILE: &str = "passport.schema";
+
+/// The part 2 rules from the puzzle, as written in `src/day4/passport.schema`.
+///
+/// This is only the default for when there isn't a schema next to the input (see `load_schema`);
+/// it's also what decides what order fields get written out in.
+// unwrap is ok because the schema is right there and the tests would catch it
+pub static DEFAULT_SCHEMA: Lazy<Schema> =
+    Lazy::new(|| include_str!("passport.schema").parse().unwrap());
+
+/// Load the part 2 schema from next to the day 4 input if there's one there,
+/// or use the default one otherwise.
+pub fn load_schema(loader: &InputLoader) -> Result<Schema> {
+    match loader.find_file(4, SCHEMA_FILE) {
+        Some(path) => Schema::load(path),
+        None => Ok(DEFAULT_SCHEMA.clone()),
     }
 }
 
-fn validate_year(year: &str, min: u16, max: u16) -> bool {
-    let year: u16 = match year.parse() {
-        Ok(it) => it,
-        Err(_heck) => return false,
-    };
-    min <= year && year <= max
-}
+impl Passport {
+    /// Validate this passport as per part 1:
+    /// every required field has to be there, but it doesn't matter what's in them.
+    pub fn validate_part1(&self, schema: &Schema) -> bool {
+        schema.missing(self).is_empty()
+    }
 
-fn validate_height(height: &str) -> bool {
-    let units_idx = match height.find(|c: char| !c.is_numeric()) {
-        Some(it) => it,
-        None => {
-            return false;
-        }
-    };
-    let (num_str, units) = height.split_at(units_idx);
-    let (min, max) = match units {
-        "in" => (59, 76),
-        "cm" => (150, 193),
-        _ => return false,
-    };
-    let num: u16 = match num_str.parse() {
-        Ok(it) => it,
-        Err(_) => return false,
-    };
-    min <= num && num <= max
-}
+    /// Validate this passport as per part 2.
+    /// returns `true` if it's valid, `false` otherwise
+    pub fn validate_part2(&self, schema: &Schema) -> bool {
+        self.validate(schema).is_ok()
+    }
 
-fn validate_hair(hair: &str) -> bool {
-    let (this_better_be_a_hash, color) = hair.split_at(1);
-    if this_better_be_a_hash != "#" {
-        return false;
+    /// Check this passport against any schema,
+    /// erroring with the first thing wrong with it.
+    pub fn validate(&self, schema: &Schema) -> Result<()> {
+        schema.validate(self)
     }
-    u64::from_str_radix(color, 16).is_ok()
-}
 
-fn validate_eye(eye: &str) -> bool {
-    ["amb", "blu", "brn", "gry", "grn", "hzl", "oth"].contains(&eye)
+    /// List everything wrong with this passport as per part 2.
+    pub fn report(&self, schema: &Schema) -> Report {
+        schema.report(self)
+    }
 }
 
-fn validate_pid(pid: &str) -> bool {
-    pid.len() == 9 && pid.parse::<u64>().is_ok()
+/// Count up how many passports failed on each field as per part 2.
+pub fn batch_report(passports: &[Passport], schema: &Schema) -> BatchReport {
+    schema.batch_report(passports)
 }
 
 #[test]
@@ -96,10 +75,38 @@ eyr:2038 hcl:74454a iyr:2023
 pid:3556412378 byr:2007
 "#;
     let invalids = Passport::parse_all(invalids_str)?;
-    for iv in invalids {
-        assert!(!iv.validate_part2());
+    for iv in invalids.iter() {
+        assert!(!iv.validate_part2(&DEFAULT_SCHEMA));
     }
 
+    assert_eq!(
+        invalids[3].report(&DEFAULT_SCHEMA).to_string(),
+        "byr: 2007 exceeds 2002
+iyr: 2023 exceeds 2020
+eyr: 2038 exceeds 2030
+hgt: 59cm is below 150
+hcl: `74454a` doesn't match `^#[0-9a-f]{6}$`
+ecl: `zzz` isn't one of amb, blu, brn, gry, grn, hzl, oth
+pid: 10 digits, expected 9"
+    );
+    assert_eq!(
+        batch_report(&invalids, &DEFAULT_SCHEMA).to_string(),
+        "0 of 4 valid
+  eyr: 3 failed
+  hcl: 2 failed
+  hgt: 2 failed
+  pid: 2 failed
+  byr: 1 failed
+  ecl: 1 failed
+  iyr: 1 failed"
+    );
+
+    let pp = Passport::parse("byr:1980 iyr:2012 eyr:2030 hgt:200cm hcl:#623a2f\n")?;
+    assert_eq!(
+        pp.report(&DEFAULT_SCHEMA).to_string(),
+        "hgt: 200cm exceeds 193\necl: missing\npid: missing"
+    );
+
     Ok(())
 }
 
@@ -120,8 +127,48 @@ iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719
 "#;
     let valids = Passport::parse_all(valids_str)?;
     for valid in valids {
-        assert!(valid.validate_part2());
+        valid.validate(&DEFAULT_SCHEMA)?;
     }
 
     Ok(())
 }
+
+#[test]
+fn test_load_schema() -> Result<()> {
+    use crate::solution::Part;
+
+    let dir = std::env::temp_dir().join(format!("aoc-day4-schema-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    let loader = InputLoader::new(&dir);
+
+    // nothing there, so it's the default
+    let schema = load_schema(&loader)?;
+    assert_eq!(schema.fields().len(), DEFAULT_SCHEMA.fields().len());
+
+    std::fs::write(
+        dir.join("day4.passport.schema"),
+        "byr required int 2000..=2001\n",
+    )?;
+    let schema = load_schema(&loader)?;
+    assert_eq!(schema.fields().len(), 1);
+
+    // the runner gets it from the same place, before it starts timing the parse
+    let entry = crate::solution::get(4).unwrap();
+    let input = "byr:1999\n\nbyr:2000\n";
+    let parsed = entry.parse_with(input, &entry.load_extra(&loader)?)?;
+    assert_eq!(entry.solve_parsed(&parsed, Part::Two)?, "1");
+    // and without it, it's the default one, which wants a lot more fields
+    let parsed = entry.parse(input)?;
+    assert_eq!(entry.solve_parsed(&parsed, Part::Two)?, "0");
+
+    std::fs::write(dir.join("day4.passport.schema"), "byr required float\n")?;
+    let err = load_schema(&loader).unwrap_err();
+    assert!(
+        format!("{:#}", err).contains("unknown type `float`"),
+        "{:#}",
+        err
+    );
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
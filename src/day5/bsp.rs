@@ -0,0 +1,296 @@
+use std::fmt::{Display, Formatter};
+
+/// One axis of a binary space partition.
+///
+/// Each letter for it halves the range that's left, with `low` keeping the lower half
+/// and `high` keeping the upper one.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Axis {
+    low: char,
+    high: char,
+    size: u32,
+}
+
+impl Axis {
+    /// Errors unless `size` is a power of two that's at least 2,
+    /// because otherwise it can't be split in half all the way down.
+    pub fn new(low: char, high: char, size: u32) -> Result<Self, BspError> {
+        if low == high {
+            return Err(BspError::SameLetters(low));
+        }
+        if size < 2 || !size.is_power_of_two() {
+            return Err(BspError::BadSize(size));
+        }
+        Ok(Self { low, high, size })
+    }
+
+    pub fn low(&self) -> char {
+        self.low
+    }
+
+    pub fn high(&self) -> char {
+        self.high
+    }
+
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// How many letters it takes to pick one spot on this axis
+    pub fn letters(&self) -> usize {
+        self.size.trailing_zeros() as usize
+    }
+}
+
+/// A code made of one axis after another, like `FBFBBFFRLR`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct BspCode {
+    axes: Vec<Axis>,
+}
+
+impl BspCode {
+    pub fn new(axes: Vec<Axis>) -> Result<Self, BspError> {
+        if axes.is_empty() {
+            return Err(BspError::NoAxes);
+        }
+        Ok(Self { axes })
+    }
+
+    /// The code on boarding passes: 128 rows by `F`/`B` then 8 columns by `L`/`R`.
+    pub fn boarding_pass() -> Self {
+        // these are definitely powers of 2
+        Self::new(vec![
+            Axis::new('F', 'B', 128).unwrap(),
+            Axis::new('L', 'R', 8).unwrap(),
+        ])
+        .unwrap()
+    }
+
+    pub fn axes(&self) -> &[Axis] {
+        &self.axes
+    }
+
+    /// How many letters long a whole code is
+    pub fn letters(&self) -> usize {
+        self.axes.iter().map(Axis::letters).sum()
+    }
+
+    /// Turn a code into a position along each axis.
+    pub fn decode(&self, code: &str) -> Result<Vec<u32>, BspError> {
+        let expected = self.letters();
+        let found = code.chars().count();
+        if found < expected {
+            return Err(BspError::TooShort { expected, found });
+        }
+        if found > expected {
+            return Err(BspError::TooLong { expected, found });
+        }
+
+        let mut letters = code.chars().enumerate();
+        self.axes
+            .iter()
+            .map(|axis| {
+                letters
+                    .by_ref()
+                    .take(axis.letters())
+                    .try_fold(0, |acc, (position, c)| {
+                        let bit = match c {
+                            c if c == axis.low => 0,
+                            c if c == axis.high => 1,
+                            oh_no => {
+                                return Err(BspError::BadLetter {
+                                    position,
+                                    found: oh_no,
+                                    low: axis.low,
+                                    high: axis.high,
+                                })
+                            }
+                        };
+                        Ok(acc * 2 + bit)
+                    })
+            })
+            .collect()
+    }
+
+    /// Turn a position along each axis back into a code.
+    pub fn encode(&self, position: &[u32]) -> Result<String, BspError> {
+        if position.len() != self.axes.len() {
+            return Err(BspError::WrongAxes {
+                expected: self.axes.len(),
+                found: position.len(),
+            });
+        }
+        let mut out = String::with_capacity(self.letters());
+        for (idx, (axis, &pos)) in self.axes.iter().zip(position).enumerate() {
+            if pos >= axis.size {
+                return Err(BspError::OffAxis {
+                    axis: idx,
+                    position: pos,
+                    size: axis.size,
+                });
+            }
+            // most significant bit first
+            out.extend((0..axis.letters()).rev().map(|bit| {
+                if pos & (1 << bit) == 0 {
+                    axis.low
+                } else {
+                    axis.high
+                }
+            }));
+        }
+        Ok(out)
+    }
+}
+
+/// Something wrong with a code, or with making or encoding one.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum BspError {
+    /// An axis was given the same letter for both halves
+    SameLetters(char),
+    /// An axis wasn't a power of two long
+    BadSize(u32),
+    /// A code was made with no axes
+    NoAxes,
+    /// A position to encode had the wrong number of axes
+    WrongAxes {
+        expected: usize,
+        found: usize,
+    },
+    /// A position to encode was off the end of one of the axes
+    OffAxis {
+        /// Which axis, starting from 0
+        axis: usize,
+        position: u32,
+        size: u32,
+    },
+    TooShort {
+        expected: usize,
+        found: usize,
+    },
+    TooLong {
+        expected: usize,
+        found: usize,
+    },
+    BadLetter {
+        /// Starting from 0
+        position: usize,
+        found: char,
+        low: char,
+        high: char,
+    },
+}
+
+impl Display for BspError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BspError::SameLetters(letter) => write!(
+                f,
+                "an axis needs two different letters, not `{}` twice",
+                letter
+            ),
+            BspError::BadSize(size) => {
+                write!(f, "an axis has to be a power of two long, not {}", size)
+            }
+            BspError::NoAxes => write!(f, "a code needs at least one axis"),
+            BspError::WrongAxes { expected, found } => write!(
+                f,
+                "expected a position on {} axes but got {}",
+                expected, found
+            ),
+            BspError::OffAxis {
+                axis,
+                position,
+                size,
+            } => write!(
+                f,
+                "{} is off the end of axis {}, which is {} long",
+                position, axis, size
+            ),
+            BspError::TooShort { expected, found } => write!(
+                f,
+                "code is too short: expected {} letters but found {}",
+                expected, found
+            ),
+            BspError::TooLong { expected, found } => write!(
+                f,
+                "code is too long: expected {} letters but found {}",
+                expected, found
+            ),
+            BspError::BadLetter {
+                position,
+                found,
+                low,
+                high,
+            } => write!(
+                f,
+                "expected `{}` or `{}` at position {} but found `{}`",
+                low, high, position, found
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BspError {}
+
+#[test]
+fn test_bsp() -> anyhow::Result<()> {
+    let code = BspCode::boarding_pass();
+    assert_eq!(code.letters(), 10);
+    assert_eq!(code.decode("FBFBBFFRLR")?, vec![44, 5]);
+    assert_eq!(code.encode(&[44, 5])?, "FBFBBFFRLR");
+
+    // something made up with 3 axes
+    let code = BspCode::new(vec![
+        Axis::new('U', 'D', 4)?,
+        Axis::new('<', '>', 2)?,
+        Axis::new('0', '1', 16)?,
+    ])?;
+    assert_eq!(code.letters(), 7);
+    assert_eq!(code.decode("DU>1010")?, vec![2, 1, 10]);
+    for position in [[0, 0, 0], [3, 1, 15], [1, 0, 6]].iter() {
+        assert_eq!(code.decode(&code.encode(position)?)?, position);
+    }
+
+    assert_eq!(
+        code.decode("DU>101"),
+        Err(BspError::TooShort {
+            expected: 7,
+            found: 6
+        })
+    );
+    assert_eq!(
+        code.decode("DU>10101"),
+        Err(BspError::TooLong {
+            expected: 7,
+            found: 8
+        })
+    );
+    assert_eq!(
+        code.decode("DU>1x10").unwrap_err().to_string(),
+        "expected `0` or `1` at position 4 but found `x`"
+    );
+    // right letters, wrong axis
+    assert!(code.decode("D>U1010").is_err());
+
+    assert_eq!(
+        code.encode(&[4, 0, 0]),
+        Err(BspError::OffAxis {
+            axis: 0,
+            position: 4,
+            size: 4
+        })
+    );
+    assert_eq!(
+        code.encode(&[0, 0]),
+        Err(BspError::WrongAxes {
+            expected: 3,
+            found: 2
+        })
+    );
+    assert_eq!(Axis::new('L', 'R', 6), Err(BspError::BadSize(6)));
+    assert_eq!(Axis::new('L', 'R', 1), Err(BspError::BadSize(1)));
+    assert_eq!(Axis::new('L', 'L', 8), Err(BspError::SameLetters('L')));
+    assert_eq!(BspCode::new(vec![]), Err(BspError::NoAxes));
+
+    Ok(())
+}
@@ -1,78 +1,168 @@
-use std::collections::HashSet;
+use anyhow::{anyhow, bail, Context, Result};
 
-const INPUT: &str = include_str!("input.txt");
+use std::{
+    collections::HashSet,
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
 
-#[derive(Debug)]
-struct Seat {
-    row: i16,
-    column: i16,
+use crate::solution::Solution;
+
+mod bsp;
+
+pub use bsp::{Axis, BspCode, BspError};
+
+pub struct Day5;
+
+impl Solution for Day5 {
+    type Parsed = Vec<Seat>;
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Parsed> {
+        input
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(idx, line)| {
+                line.trim()
+                    .parse()
+                    .with_context(|| format!("bad seat on line {}", idx + 1))
+            })
+            .collect()
+    }
+
+    fn part1(seats: &Self::Parsed) -> Result<u32> {
+        seats
+            .iter()
+            .map(|seat| seat.seat_id())
+            .max()
+            .ok_or_else(|| anyhow!("there were no seats"))
+    }
+
+    fn part2(seats: &Self::Parsed) -> Result<u32> {
+        let ids_present = seats
+            .iter()
+            .map(|seat| seat.seat_id())
+            .collect::<HashSet<_>>();
+
+        let max_id = Seat {
+            row: Seat::ROWS - 1,
+            column: Seat::COLUMNS - 1,
+        }
+        .seat_id();
+        // the seats on either side have to be there, so it can't be the first or last
+        (1..max_id)
+            .find(|id| {
+                !ids_present.contains(id)
+                    && ids_present.contains(&(id - 1))
+                    && ids_present.contains(&(id + 1))
+            })
+            .ok_or_else(|| anyhow!("there was no empty seat between two full ones"))
+    }
+}
+
+/// A seat on the plane.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Seat {
+    row: u32,
+    column: u32,
 }
 
 impl Seat {
     /// How many rows are there?
-    const ROWS: i16 = 128;
-    /// How manu columns are there?
-    const COLUMNS: i16 = 8;
-
-    /// Turn an input string into a Seat.
-    /// yes this should be from_str whatever
-    fn parse(i: &str) -> Seat {
-        let col_start_idx = i.find(|c: char| c == 'L' || c == 'R').unwrap();
-        let (row, col) = i.split_at(col_start_idx);
-        let row = partition(row, 'F', 'B');
-        let column = partition(col, 'L', 'R');
-        Seat { row, column }
+    const ROWS: u32 = 128;
+    /// How many columns are there?
+    const COLUMNS: u32 = 8;
+
+    pub fn new(row: u32, column: u32) -> Result<Self> {
+        if row >= Seat::ROWS || column >= Seat::COLUMNS {
+            bail!("there's no seat at row {}, column {}", row, column);
+        }
+        Ok(Seat { row, column })
+    }
+
+    pub fn row(&self) -> u32 {
+        self.row
+    }
+
+    pub fn column(&self) -> u32 {
+        self.column
     }
 
     /// Get the seat ID of this.
-    fn seat_id(&self) -> i16 {
-        self.row * 8 + self.column
+    pub fn seat_id(&self) -> u32 {
+        self.row * Seat::COLUMNS + self.column
+    }
+
+    /// Turn this back into what it says on a boarding pass.
+    pub fn code(&self) -> String {
+        // a seat is always on the plane, so it always has a code
+        BspCode::boarding_pass()
+            .encode(&[self.row, self.column])
+            .unwrap()
+    }
+}
+
+impl FromStr for Seat {
+    type Err = BspError;
+    /// Turn a boarding pass like `FBFBBFFRLR` into a Seat.
+    fn from_str(s: &str) -> Result<Self, BspError> {
+        // there's always 2 axes
+        let position = BspCode::boarding_pass().decode(s)?;
+        Ok(Seat {
+            row: position[0],
+            column: position[1],
+        })
+    }
+}
+
+impl Display for Seat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.code())
     }
 }
 
-/// Partition into a value
-fn partition(input: &str, take_low: char, take_high: char) -> i16 {
-    input
-        .chars()
-        .take_while(|&c| c == take_low || c == take_high)
-        .fold(0, |acc, c| acc * 2 + (c == take_high) as i16)
+#[test]
+fn part1() -> Result<()> {
+    let seats = Day5::parse(&test_input!(5))?;
+    let answer = Day5::part1(&seats)?;
+    println!("max seat id: {}", answer);
+    check_answer!(5, One, answer);
+
+    Ok(())
 }
 
 #[test]
-fn part1() {
-    let seats = INPUT
-        .lines()
-        .map(|line| Seat::parse(line))
-        .collect::<Vec<_>>();
-
-    let max_seatid = seats.iter().map(|seat| seat.seat_id()).max().unwrap();
-    println!("max seat id: {}", max_seatid);
+fn part2() -> Result<()> {
+    let seats = Day5::parse(&test_input!(5))?;
+    let answer = Day5::part2(&seats)?;
+    println!("my seat ID is {}", answer);
+    check_answer!(5, Two, answer);
+
+    Ok(())
 }
 
 #[test]
-fn part2() {
-    let seats = INPUT
-        .lines()
-        .map(|line| Seat::parse(line))
-        .collect::<Vec<_>>();
-    let ids_present = seats
-        .iter()
-        .map(|seat| seat.seat_id())
-        .collect::<HashSet<_>>();
-
-    let max_id = Seat {
-        row: Seat::ROWS - 1,
-        column: Seat::COLUMNS - 1,
-    }
-    .seat_id();
-    for id in 0..=max_id {
-        if !ids_present.contains(&id)
-            && ids_present.contains(&(id - 1))
-            && ids_present.contains(&(id + 1))
-        {
-            // we got it!
-            println!("my seat ID is {}", id);
-            break;
-        }
-    }
+fn example() -> Result<()> {
+    let seats = Day5::parse("BFFFBBFRRR\r\nFFFBBBFRRR\r\nBBFFBBFRLL\r\n")?;
+    assert_eq!(
+        seats.iter().map(Seat::seat_id).collect::<Vec<_>>(),
+        vec![567, 119, 820]
+    );
+    assert_eq!(Day5::part1(&seats)?, 820);
+    assert_eq!(seats[0].row(), 70);
+    assert_eq!(seats[0].to_string(), "BFFFBBFRRR");
+    assert_eq!(Seat::new(127, 7)?.code(), "BBBBBBBRRR");
+    assert!(Seat::new(128, 0).is_err());
+
+    let err = Day5::parse("BFFFBBFRRR\nBFFFBBFRRRL").unwrap_err();
+    assert_eq!(
+        format!("{:#}", err),
+        "bad seat on line 2: code is too long: expected 10 letters but found 11"
+    );
+    assert!("BFFFBBFRRX".parse::<Seat>().is_err());
+    assert!("BFFFBBFRR".parse::<Seat>().is_err());
+
+    Ok(())
 }
@@ -1,35 +1,82 @@
+use anyhow::{anyhow, Result};
+
 use std::collections::HashSet;
 
-const INPUT: &str = include_str!("input.txt");
+use crate::{records, solution::Solution};
 
-#[test]
-fn part1() {
-    let groups = INPUT.split("\n\n");
-    let group_sets = groups
+pub struct Day6;
+
+impl Solution for Day6 {
+    /// Each group, holding the set of questions each person in it answered yes to
+    type Parsed = Vec<Vec<HashSet<char>>>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Parsed> {
+        Ok(records::groups(input)
+            .iter()
+            .map(|group| {
+                group
+                    .lines()
+                    .map(|line| line.chars().collect::<HashSet<_>>())
+                    .collect()
+            })
+            .collect())
+    }
+
+    fn part1(groups: &Self::Parsed) -> Result<usize> {
+        count_groups(groups, |acc, set| acc.union(set).cloned().collect())
+    }
+
+    fn part2(groups: &Self::Parsed) -> Result<usize> {
+        count_groups(groups, |acc, set| acc.intersection(set).cloned().collect())
+    }
+}
+
+/// Combine the answers in each group and add up how many there are.
+fn count_groups(
+    groups: &[Vec<HashSet<char>>],
+    combine: impl Fn(&HashSet<char>, &HashSet<char>) -> HashSet<char>,
+) -> Result<usize> {
+    groups
+        .iter()
         .map(|group| {
             group
-                .lines()
-                .map(|line| line.chars().collect::<HashSet<_>>())
-                .fold_first(|acc, set| acc.union(&set).cloned().collect())
-                .unwrap()
+                .iter()
+                .cloned()
+                .reduce(|acc, set| combine(&acc, &set))
+                .map(|set| set.len())
+                .ok_or_else(|| anyhow!("there was an empty group"))
         })
-        .collect::<Vec<_>>();
-    let total_count: usize = group_sets.iter().map(|set| set.len()).sum();
-    println!("total count: {}", total_count);
+        .sum()
 }
 
 #[test]
-fn part2() {
-    let groups = INPUT.split("\n\n");
-    let group_sets = groups
-        .map(|group| {
-            group
-                .lines()
-                .map(|line| line.chars().collect::<HashSet<_>>())
-                .fold_first(|acc, set| acc.intersection(&set).cloned().collect())
-                .unwrap()
-        })
-        .collect::<Vec<_>>();
-    let total_count: usize = group_sets.iter().map(|set| set.len()).sum();
-    println!("total intersection count: {}", total_count);
+fn part1() -> Result<()> {
+    let groups = Day6::parse(&test_input!(6))?;
+    let answer = Day6::part1(&groups)?;
+    println!("total count: {}", answer);
+    check_answer!(6, One, answer);
+
+    Ok(())
+}
+
+#[test]
+fn part2() -> Result<()> {
+    let groups = Day6::parse(&test_input!(6))?;
+    let answer = Day6::part2(&groups)?;
+    println!("total intersection count: {}", answer);
+    check_answer!(6, Two, answer);
+
+    Ok(())
+}
+
+#[test]
+fn example() -> Result<()> {
+    let groups =
+        Day6::parse("abc\r\n\r\na\r\nb\r\nc\r\n\r\nab\r\nac\r\n\r\na\r\na\r\na\r\na\r\n\r\nb\r\n")?;
+    assert_eq!(Day6::part1(&groups)?, 11);
+    assert_eq!(Day6::part2(&groups)?, 6);
+
+    Ok(())
 }
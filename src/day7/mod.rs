@@ -4,16 +4,42 @@ use anyhow::Result;
 
 use std::collections::HashMap;
 
-const INPUT: &str = include_str!("input.txt");
+use crate::solution::Solution;
+
+pub struct Day7;
+
+impl Solution for Day7 {
+    type Parsed = BagCollection;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<BagCollection> {
+        BagCollection::parse_input(input)
+    }
+
+    fn part1(bags: &BagCollection) -> Result<usize> {
+        Ok(bags.search(&Pattern {
+            quality: "shiny".to_string(),
+            color: "gold".to_string(),
+        }))
+    }
+
+    fn part2(bags: &BagCollection) -> Result<usize> {
+        Ok(bags.count(&Pattern {
+            quality: "shiny".to_string(),
+            color: "gold".to_string(),
+        }))
+    }
+}
 
 /// no mating
 #[derive(Debug, Clone, Hash, Eq, PartialEq)]
-struct Pattern {
+pub struct Pattern {
     pub quality: String,
     pub color: String,
 }
 
-struct BagCollection {
+pub struct BagCollection {
     /// Maps bag patterns to the counts and patterns it can contain
     bags: HashMap<Pattern, Vec<(usize, Pattern)>>,
 }
@@ -58,7 +84,7 @@ impl BagCollection {
         }
 
         bags.keys()
-            .filter(|pat| search_inner(target, pat, &bags, &mut cache))
+            .filter(|pat| search_inner(target, pat, bags, &mut cache))
             .count()
     }
 
@@ -98,24 +124,20 @@ impl BagCollection {
 
 #[test]
 fn part1() -> Result<()> {
-    let bags = BagCollection::parse_input(INPUT)?;
-    let count = bags.search(&Pattern {
-        quality: "shiny".to_string(),
-        color: "gold".to_string(),
-    });
-    println!("count: {}", count);
+    let bags = Day7::parse(&test_input!(7))?;
+    let answer = Day7::part1(&bags)?;
+    println!("count: {}", answer);
+    check_answer!(7, One, answer);
 
     Ok(())
 }
 
 #[test]
 fn part2() -> Result<()> {
-    let bags = BagCollection::parse_input(INPUT)?;
-    let count = bags.count(&Pattern {
-        quality: "shiny".to_string(),
-        color: "gold".to_string(),
-    });
-    println!("count: {}", count);
+    let bags = Day7::parse(&test_input!(7))?;
+    let answer = Day7::part2(&bags)?;
+    println!("count: {}", answer);
+    check_answer!(7, Two, answer);
 
     Ok(())
 }
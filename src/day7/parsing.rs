@@ -38,7 +38,7 @@ fn parse_line(line: &str, line_num: usize) -> Result<Bag> {
     // 1 bright white bag, 2 muted yellow bags.
     // OR
     // no other bags.
-    if let Some(&"no") = i.get(0) {
+    if let Some(&"no") = i.first() {
         // No other bags!
         return Ok(Bag {
             pattern,
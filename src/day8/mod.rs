@@ -1,44 +1,59 @@
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
 
-use crate::intcodent::{Executor, Operation};
+use crate::{
+    intcodent::{analysis, Executor, Instruction, Termination},
+    solution::Solution,
+};
 
-const INPUT: &str = include_str!("input.txt");
+pub struct Day8;
+
+impl Solution for Day8 {
+    type Parsed = Vec<Instruction>;
+    type Answer1 = i64;
+    type Answer2 = i64;
+
+    fn parse(input: &str) -> Result<Self::Parsed> {
+        Executor::parse_program(input)
+    }
+
+    fn part1(program: &Self::Parsed) -> Result<i64> {
+        let mut exe = Executor::new_from_program(program.clone());
+        match exe.run_until_end_or_loop()? {
+            Termination::Looped(acc) => Ok(acc),
+            Termination::Ended(_) => bail!("the program terminated without looping"),
+        }
+    }
+
+    fn part2(program: &Self::Parsed) -> Result<i64> {
+        let patch = analysis::find_repair(program)?
+            .ok_or_else(|| anyhow!("no single swap made the program terminate"))?;
+
+        let mut program = program.clone();
+        patch.apply(&mut program);
+        let mut exe = Executor::new_from_program(program);
+        match exe.run_until_end_or_loop()? {
+            Termination::Ended(acc) => Ok(acc),
+            Termination::Looped(_) => bail!("swapping {} didn't make it terminate", patch),
+        }
+    }
+}
 
 #[test]
 fn part1() -> Result<()> {
-    let res = Executor::run_until_loop(INPUT)?;
-    println!("result: {}", res);
+    let program = Day8::parse(&test_input!(8))?;
+    let answer = Day8::part1(&program)?;
+    println!("result: {}", answer);
+    check_answer!(8, One, answer);
 
     Ok(())
 }
 
 #[test]
 fn part2() -> Result<()> {
-    let program = Executor::parse_program(INPUT)?;
-
-    // For each index in the program, change its opcode.
-    // and try to execute it
-    for change_idx in 0..program.len() {
-        let mut program = program.clone();
-        let swapped_opcode = match &program[change_idx].operation {
-            Operation::Jmp => Operation::Nop,
-            Operation::Nop => Operation::Jmp,
-            _ => continue,
-        };
-        program[change_idx].operation = swapped_opcode;
-
-        let mut exe = Executor::new_from_program(program);
-        let output = exe.run_until_end_or_loop();
-        if let Ok(output) = output {
-            println!(
-                "by changing line {} to {:?}, output is {}",
-                change_idx + 1,
-                swapped_opcode,
-                output
-            );
-            break;
-        }
-    }
+    let program = Day8::parse(&test_input!(8))?;
+    let answer = Day8::part2(&program)?;
+    println!("result: {}", answer);
+    check_answer!(8, Two, answer);
 
     Ok(())
 }
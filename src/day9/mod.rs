@@ -1,10 +1,36 @@
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Result};
 use itertools::Itertools;
 use multiset::HashMultiSet;
 
 use std::collections::VecDeque;
 
-const INPUT: &str = include_str!("input.txt");
+use crate::solution::Solution;
+
+/// How many numbers come before the ones we check
+const PREAMBLE_LEN: usize = 25;
+
+pub struct Day9;
+
+impl Solution for Day9 {
+    type Parsed = Vec<i64>;
+    type Answer1 = i64;
+    type Answer2 = i64;
+
+    fn parse(input: &str) -> Result<Self::Parsed> {
+        input.lines().map(|line| Ok(line.parse()?)).collect()
+    }
+
+    fn part1(transmission: &Self::Parsed) -> Result<i64> {
+        find_invalid(transmission, PREAMBLE_LEN)?
+            .ok_or_else(|| anyhow!("every number was a sum of two previous numbers"))
+    }
+
+    fn part2(transmission: &Self::Parsed) -> Result<i64> {
+        let invalid = Day9::part1(transmission)?;
+        find_weakness(transmission, invalid)
+            .ok_or_else(|| anyhow!("no range summed to {}", invalid))
+    }
+}
 
 struct Decoder {
     /// Buffer of received numbers.
@@ -64,45 +90,31 @@ impl Decoder {
     }
 }
 
-#[test]
-fn part1() -> Result<()> {
-    let transmission = INPUT
-        .lines()
-        .map(|line| Ok(line.parse()?))
-        .collect::<Result<Vec<i64>>>()?;
-    let (preamble, tail) = transmission.split_at(25);
+/// Find the first number in the transmission that isn't the sum of two of the
+/// `preamble_len` numbers before it.
+fn find_invalid(transmission: &[i64], preamble_len: usize) -> Result<Option<i64>> {
+    if transmission.len() < preamble_len {
+        bail!(
+            "transmission is {} long but the preamble is {}",
+            transmission.len(),
+            preamble_len
+        );
+    }
+    let (preamble, tail) = transmission.split_at(preamble_len);
 
     let mut decoder = Decoder::new(preamble);
     for &num in tail {
         let sum_ok = decoder.receive(num)?;
         if !sum_ok {
-            println!("{} was not present in the sums!", num);
-            break;
+            return Ok(Some(num));
         }
     }
-
-    Ok(())
+    Ok(None)
 }
 
-#[test]
-fn part2() -> Result<()> {
-    let transmission = INPUT
-        .lines()
-        .map(|line| Ok(line.parse()?))
-        .collect::<Result<Vec<i64>>>()?;
-    let (preamble, tail) = transmission.split_at(25);
-
-    let mut decoder = Decoder::new(preamble);
-    let invalid = *tail
-        .iter()
-        .map(|&num| decoder.receive(num).map(|success| (num, success)))
-        .map_results(|(num, success)| if !success { Some(num) } else { None })
-        .collect::<Result<Vec<_>>>()?
-        .iter()
-        .find_map(|it| it.as_ref())
-        .unwrap();
-    println!("our illegal number is {}", invalid);
-
+/// Find a contiguous range of at least two numbers summing to `invalid`,
+/// and add up the smallest and largest numbers in it.
+fn find_weakness(transmission: &[i64], invalid: i64) -> Option<i64> {
     for i in 0..transmission.len() {
         for j in (i + 1)..transmission.len() {
             let range = &transmission[i..=j];
@@ -110,10 +122,29 @@ fn part2() -> Result<()> {
             if sum == invalid {
                 // poggers
                 let (low, high) = range.iter().minmax().into_option().unwrap();
-                println!("weakness: {} from [{}, {}]", low + high, i, j);
+                return Some(low + high);
             }
         }
     }
+    None
+}
+
+#[test]
+fn part1() -> Result<()> {
+    let transmission = Day9::parse(&test_input!(9))?;
+    let answer = Day9::part1(&transmission)?;
+    println!("{} was not present in the sums!", answer);
+    check_answer!(9, One, answer);
+
+    Ok(())
+}
+
+#[test]
+fn part2() -> Result<()> {
+    let transmission = Day9::parse(&test_input!(9))?;
+    let answer = Day9::part2(&transmission)?;
+    println!("weakness: {}", answer);
+    check_answer!(9, Two, answer);
 
     Ok(())
 }
@@ -140,20 +171,10 @@ fn part1_test() -> Result<()> {
 277
 309
 576";
-    let transmission = input
-        .lines()
-        .map(|line| Ok(line.parse()?))
-        .collect::<Result<Vec<i64>>>()?;
-    let (preamble, tail) = transmission.split_at(5);
-
-    let mut decoder = Decoder::new(preamble);
-    for &num in tail {
-        let sum_ok = decoder.receive(num)?;
-        if !sum_ok {
-            println!("{} was not present in the sums!", num);
-            break;
-        }
-    }
+    let transmission = Day9::parse(input)?;
+    let invalid = find_invalid(&transmission, 5)?;
+    assert_eq!(invalid, Some(127));
+    assert_eq!(find_weakness(&transmission, 127), Some(62));
 
     Ok(())
 }
@@ -0,0 +1,397 @@
+//! A rectangle of things, for the days that are maps made of characters.
+//!
+//! Cells are stored flat, `y * width + x`, with x increasing to the right and y down.
+
+use std::{
+    fmt::{Display, Formatter},
+    ops::{Index, IndexMut},
+};
+
+use anyhow::{anyhow, bail, Result};
+
+/// The four cells sharing an edge, starting north and going clockwise
+pub const ORTHOGONAL: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+/// The eight cells sharing an edge or a corner, starting north-west and going clockwise
+pub const ALL_AROUND: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+];
+
+/// What happens when you go off the side of a grid
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Edges {
+    /// There's nothing out there
+    Bounded,
+    /// Going off one side brings you back on the other, left-right and up-down
+    Wrap,
+    /// Only wrap left-right; up and down are bounded
+    WrapX,
+    /// Only wrap up-down; left and right are bounded
+    WrapY,
+}
+
+/// Something that can be written as a single character in a map.
+pub trait Tile: Sized {
+    /// Turn a character into a tile, or None if it isn't one
+    fn from_char(c: char) -> Option<Self>;
+    fn to_char(&self) -> char;
+}
+
+/// `#` is true and `.` is false, like in most of the puzzles
+impl Tile for bool {
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        }
+    }
+
+    fn to_char(&self) -> char {
+        if *self {
+            '#'
+        } else {
+            '.'
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    edges: Edges,
+    /// `y * width + x`
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Make a bounded grid full of copies of one thing.
+    /// Errors if it's 0 wide, because then there'd be no way of telling how tall it is.
+    pub fn new(width: usize, height: usize, fill: T) -> Result<Self>
+    where
+        T: Clone,
+    {
+        if width == 0 {
+            bail!("a grid can't be 0 wide");
+        }
+        let len = width
+            .checked_mul(height)
+            .ok_or_else(|| anyhow!("a grid can't be {} by {}", width, height))?;
+        Ok(Self {
+            width,
+            height,
+            edges: Edges::Bounded,
+            cells: vec![fill; len],
+        })
+    }
+
+    /// Make a bounded grid out of the cells in `y * width + x` order
+    pub fn from_vec(width: usize, cells: Vec<T>) -> Result<Self> {
+        if width == 0 || !cells.len().is_multiple_of(width) {
+            bail!(
+                "{} cells can't be made into rows {} wide",
+                cells.len(),
+                width
+            );
+        }
+        Ok(Self {
+            width,
+            height: cells.len() / width,
+            edges: Edges::Bounded,
+            cells,
+        })
+    }
+
+    /// Parse a bounded grid from one row per line, turning each character into a cell with `f`.
+    ///
+    /// Every line has to be the same width, and there has to be at least one.
+    pub fn parse_with(input: &str, mut f: impl FnMut(char) -> Option<T>) -> Result<Self> {
+        let mut width = None;
+        let mut cells = Vec::new();
+        for (y, line) in input.lines().enumerate() {
+            let start = cells.len();
+            for (x, c) in line.chars().enumerate() {
+                match f(c) {
+                    Some(it) => cells.push(it),
+                    None => bail!("unknown character `{}` at ({}, {})", c, x, y),
+                }
+            }
+            let this_width = cells.len() - start;
+            match width {
+                None => width = Some(this_width),
+                Some(width) if width != this_width => bail!(
+                    "line {} is {} wide, but the first line was {} wide",
+                    y + 1,
+                    this_width,
+                    width
+                ),
+                Some(_) => {}
+            }
+        }
+
+        match width {
+            Some(width) if width > 0 => Self::from_vec(width, cells),
+            _ => Err(anyhow!("there's nothing in the grid")),
+        }
+    }
+
+    /// Set what happens at the edges
+    pub fn with_edges(mut self, edges: Edges) -> Self {
+        self.edges = edges;
+        self
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn edges(&self) -> Edges {
+        self.edges
+    }
+
+    /// Work out where a (possibly off the edge) coordinate really is,
+    /// or None if it's off a bounded edge.
+    pub fn resolve(&self, x: isize, y: isize) -> Option<(usize, usize)> {
+        let (wrap_x, wrap_y) = match self.edges {
+            Edges::Bounded => (false, false),
+            Edges::Wrap => (true, true),
+            Edges::WrapX => (true, false),
+            Edges::WrapY => (false, true),
+        };
+        let fix = |it: isize, size: usize, wrap: bool| {
+            if size == 0 {
+                // nothing to wrap round to
+                None
+            } else if wrap {
+                Some(it.rem_euclid(size as isize) as usize)
+            } else if 0 <= it && (it as usize) < size {
+                Some(it as usize)
+            } else {
+                None
+            }
+        };
+        Some((fix(x, self.width, wrap_x)?, fix(y, self.height, wrap_y)?))
+    }
+
+    /// Get the cell at a coordinate, following the edge rules
+    pub fn get(&self, x: isize, y: isize) -> Option<&T> {
+        let (x, y) = self.resolve(x, y)?;
+        Some(&self.cells[y * self.width + x])
+    }
+
+    /// Get the cell at a coordinate mutably, following the edge rules
+    pub fn get_mut(&mut self, x: isize, y: isize) -> Option<&mut T> {
+        let (x, y) = self.resolve(x, y)?;
+        Some(&mut self.cells[y * self.width + x])
+    }
+
+    /// Every coordinate in the grid, row by row
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.cells.len()).map(move |idx| (idx % width, idx / width))
+    }
+
+    /// Every cell along with its coordinate, row by row
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    /// Each row as a slice
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width)
+    }
+
+    /// Make a grid of the same shape and edges with `f` applied to every cell
+    pub fn map<U>(&self, mut f: impl FnMut((usize, usize), &T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            edges: self.edges,
+            cells: self.iter().map(|(pos, it)| f(pos, it)).collect(),
+        }
+    }
+
+    /// The cells at these offsets from (x, y), skipping any that are off a bounded edge.
+    ///
+    /// If the grid wraps and is tiny the same cell can come up more than once.
+    pub fn neighbors<'a>(
+        &'a self,
+        x: usize,
+        y: usize,
+        offsets: &'a [(isize, isize)],
+    ) -> impl Iterator<Item = (usize, usize)> + 'a {
+        offsets
+            .iter()
+            .filter_map(move |&(dx, dy)| self.resolve(x as isize + dx, y as isize + dy))
+    }
+
+    /// The 4 cells sharing an edge with (x, y)
+    pub fn neighbors4(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.neighbors(x, y, &ORTHOGONAL)
+    }
+
+    /// The 8 cells sharing an edge or a corner with (x, y)
+    pub fn neighbors8(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.neighbors(x, y, &ALL_AROUND)
+    }
+
+    /// Look from (x, y) in a direction, and find the first cell that `stop` says blocks the view.
+    ///
+    /// Returns None if the view falls off the edge first, or if it wraps all the way round.
+    pub fn line_of_sight(
+        &self,
+        x: usize,
+        y: usize,
+        (dx, dy): (isize, isize),
+        stop: impl Fn(&T) -> bool,
+    ) -> Option<(usize, usize)> {
+        let (mut cx, mut cy) = (x as isize, y as isize);
+        // you can't go through more cells than there are without repeating
+        for _ in 0..self.cells.len() {
+            cx += dx;
+            cy += dy;
+            let pos = self.resolve(cx, cy)?;
+            if pos == (x, y) {
+                return None;
+            }
+            if stop(&self[pos]) {
+                return Some(pos);
+            }
+        }
+        None
+    }
+
+    /// The first blocking cell in each of the 8 directions around (x, y), if there is one
+    pub fn visible<'a>(
+        &'a self,
+        x: usize,
+        y: usize,
+        stop: impl Fn(&T) -> bool + Copy + 'a,
+    ) -> impl Iterator<Item = (usize, usize)> + 'a {
+        ALL_AROUND
+            .iter()
+            .filter_map(move |&dir| self.line_of_sight(x, y, dir, stop))
+    }
+
+    /// Draw the grid with one character per cell and a newline after each row
+    pub fn render(&self, mut f: impl FnMut(&T) -> char) -> String {
+        let mut out = String::with_capacity((self.width + 1) * self.height);
+        for row in self.rows() {
+            out.extend(row.iter().map(&mut f));
+            out.push('\n');
+        }
+        out
+    }
+}
+
+impl<T: Tile> Grid<T> {
+    /// Parse a bounded grid from one row per line
+    pub fn parse(input: &str) -> Result<Self> {
+        Self::parse_with(input, T::from_char)
+    }
+}
+
+impl<T: Tile> Display for Grid<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.render(T::to_char))
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+    /// Panics if it's out of bounds, whatever the edges are
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        assert!(
+            x < self.width && y < self.height,
+            "({}, {}) is out of bounds",
+            x,
+            y
+        );
+        &self.cells[y * self.width + x]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        assert!(
+            x < self.width && y < self.height,
+            "({}, {}) is out of bounds",
+            x,
+            y
+        );
+        &mut self.cells[y * self.width + x]
+    }
+}
+
+#[test]
+fn test_grid() -> Result<()> {
+    let grid = Grid::<bool>::parse("#..\n.#.\n..#\n#.#")?;
+    assert_eq!((grid.width(), grid.height()), (3, 4));
+    assert_eq!(grid.to_string(), "#..\n.#.\n..#\n#.#\n");
+    assert!(grid[(2, 2)]);
+    assert_eq!(grid.get(-1, 0), None);
+    assert_eq!(grid.get(3, 0), None);
+    assert_eq!(grid.iter().filter(|(_, &it)| it).count(), 5);
+
+    assert_eq!(
+        grid.neighbors4(0, 0).collect::<Vec<_>>(),
+        vec![(1, 0), (0, 1)]
+    );
+    assert_eq!(grid.neighbors8(1, 1).count(), 8);
+
+    // looking along the diagonal from the corner
+    assert_eq!(grid.line_of_sight(0, 0, (1, 1), |&it| it), Some((1, 1)));
+    assert_eq!(grid.line_of_sight(0, 0, (1, 0), |&it| it), None);
+    assert_eq!(grid.visible(1, 3, |&it| it).count(), 4);
+
+    let wrapped = grid.clone().with_edges(Edges::WrapX);
+    assert_eq!(wrapped.get(-1, 0), Some(&false));
+    assert_eq!(wrapped.get(5, 2), Some(&true));
+    assert_eq!(wrapped.get(0, 4), None);
+    assert_eq!(
+        wrapped.neighbors4(0, 0).collect::<Vec<_>>(),
+        vec![(1, 0), (0, 1), (2, 0)]
+    );
+    // going right from (0, 3) wraps round to (2, 3)
+    assert_eq!(wrapped.line_of_sight(0, 3, (1, 0), |&it| it), Some((2, 3)));
+    // and there's nothing else in row 1, so it comes back round without finding anything
+    assert_eq!(wrapped.line_of_sight(1, 1, (1, 0), |&it| it), None);
+
+    let torus = grid.clone().with_edges(Edges::Wrap);
+    assert_eq!(torus.get(-3, -4), Some(&true));
+
+    let counts = grid.map(|(x, y), _| grid.neighbors8(x, y).filter(|&pos| grid[pos]).count());
+    assert_eq!(
+        counts.render(|&n| std::char::from_digit(n as u32, 10).unwrap()),
+        "121\n222\n242\n031\n"
+    );
+
+    assert!(Grid::<bool>::parse("#.\n#").is_err());
+    assert!(Grid::<bool>::parse("#x").is_err());
+    assert!(Grid::<bool>::parse("").is_err());
+    assert!(Grid::from_vec(3, vec![1, 2, 3, 4]).is_err());
+
+    assert!(Grid::new(0, 3, false).is_err());
+    assert!(Grid::new(usize::MAX, 2, false).is_err());
+    let blank = Grid::new(2, 3, 7)?;
+    assert_eq!(
+        blank.render(|&n| std::char::from_digit(n, 10).unwrap()),
+        "77\n77\n77\n"
+    );
+    let flat = Grid::new(4, 0, false)?.with_edges(Edges::Wrap);
+    assert_eq!(flat.get(1, 0), None);
+    assert_eq!(flat.rows().count(), 0);
+
+    Ok(())
+}
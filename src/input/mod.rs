@@ -0,0 +1,191 @@
+//! Finding and loading puzzle inputs at runtime.
+//!
+//! Inputs aren't checked in, so instead of baking them in with `include_str!`
+//! we go looking for them on disk when they're needed.
+//! For day `N`, a loader checks `<dir>/dayN/input.txt` and then `<dir>/dayN.txt`.
+//! Answers recorded for an input (see [`crate::answers`]) live next to it,
+//! in `<dir>/dayN/answers.txt` or `<dir>/dayN.answers.txt`.
+
+use anyhow::{anyhow, Context, Result};
+
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+/// Environment variable that overrides the directory inputs are looked for in.
+pub const INPUT_DIR_VAR: &str = "AOC_INPUT_DIR";
+
+/// Finds puzzle inputs in a directory.
+#[derive(Debug, Clone)]
+pub struct InputLoader {
+    dir: PathBuf,
+}
+
+impl InputLoader {
+    /// Make a loader that looks in the given directory
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Make a loader that looks in the directory in `AOC_INPUT_DIR`,
+    /// or in `src/` of this crate if that isn't set
+    /// (aka where all the `input.txt`s used to live).
+    pub fn from_env() -> Self {
+        match env::var_os(INPUT_DIR_VAR) {
+            Some(dir) => Self::new(dir),
+            None => Self::new(Path::new(env!("CARGO_MANIFEST_DIR")).join("src")),
+        }
+    }
+
+    /// Get the directory this looks in.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// All the places the input for a day might be, in the order they're checked.
+    pub fn candidates(&self, day: u32) -> [PathBuf; 2] {
+        [
+            self.dir.join(format!("day{}", day)).join("input.txt"),
+            self.dir.join(format!("day{}.txt", day)),
+        ]
+    }
+
+    /// Find the path to the input for a day, or None if there isn't one.
+    pub fn find(&self, day: u32) -> Option<PathBuf> {
+        self.candidates(day)
+            .iter()
+            .find(|path| path.is_file())
+            .cloned()
+    }
+
+    /// All the places the answers file for a day might be, in the order they're checked.
+    pub fn answers_candidates(&self, day: u32) -> [PathBuf; 2] {
+        [
+            self.dir.join(format!("day{}", day)).join("answers.txt"),
+            self.dir.join(format!("day{}.answers.txt", day)),
+        ]
+    }
+
+    /// Find the path to the answers file for a day, or None if there isn't one.
+    pub fn find_answers(&self, day: u32) -> Option<PathBuf> {
+        self.answers_candidates(day)
+            .iter()
+            .find(|path| path.is_file())
+            .cloned()
+    }
+
+    /// All the places some other file for a day might be, in the order they're checked:
+    /// `<dir>/dayN/<name>` and then `<dir>/dayN.<name>`.
+    pub fn file_candidates(&self, day: u32, name: &str) -> [PathBuf; 2] {
+        [
+            self.dir.join(format!("day{}", day)).join(name),
+            self.dir.join(format!("day{}.{}", day, name)),
+        ]
+    }
+
+    /// Find the path to some other file for a day, or None if there isn't one.
+    pub fn find_file(&self, day: u32, name: &str) -> Option<PathBuf> {
+        self.file_candidates(day, name)
+            .iter()
+            .find(|path| path.is_file())
+            .cloned()
+    }
+
+    /// Load whichever of the given files there are for a day.
+    /// The ones that aren't there are left out; it's up to the day what to do without them.
+    pub fn load_extra(&self, day: u32, names: &[&str]) -> Result<ExtraFiles> {
+        let mut extra = ExtraFiles::default();
+        for &name in names {
+            if let Some(path) = self.find_file(day, name) {
+                let contents = fs::read_to_string(&path).with_context(|| {
+                    format!(
+                        "could not read {} for day {} at `{}`",
+                        name,
+                        day,
+                        path.display()
+                    )
+                })?;
+                extra.insert(name, contents);
+            }
+        }
+        Ok(extra)
+    }
+
+    /// Load the input for a day.
+    pub fn load(&self, day: u32) -> Result<String> {
+        let path = self.find(day).ok_or_else(|| {
+            let tried = self
+                .candidates(day)
+                .iter()
+                .map(|path| format!("`{}`", path.display()))
+                .collect::<Vec<_>>()
+                .join(" or ");
+            anyhow!(
+                "no input for day {}: looked for {} (set {} to change where to look)",
+                day,
+                tried,
+                INPUT_DIR_VAR
+            )
+        })?;
+        fs::read_to_string(&path).with_context(|| {
+            format!(
+                "could not read input for day {} at `{}`",
+                day,
+                path.display()
+            )
+        })
+    }
+}
+
+/// Files besides the input that a day needs, like day 4's passport schema,
+/// loaded from next to the input by `InputLoader::load_extra`.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct ExtraFiles {
+    /// Name -> contents
+    files: HashMap<String, String>,
+}
+
+impl ExtraFiles {
+    /// Get the contents of a file, if it was there
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.files.get(name).map(String::as_str)
+    }
+
+    /// Add a file, replacing any that was there with the same name
+    pub fn insert(&mut self, name: impl Into<String>, contents: impl Into<String>) {
+        self.files.insert(name.into(), contents.into());
+    }
+}
+
+/// Load the input for a day using the default loader.
+pub fn load(day: u32) -> Result<String> {
+    InputLoader::from_env().load(day)
+}
+
+/// Load a day's input for a test.
+///
+/// If there isn't one, print why and return from the test successfully;
+/// the inputs aren't in the repo so there's nothing to check against.
+#[cfg(test)]
+macro_rules! test_input {
+    ($day:expr) => {
+        match $crate::input::load($day) {
+            Ok(it) => it,
+            Err(e) => {
+                println!("skipping: {}", e);
+                return Ok(());
+            }
+        }
+    };
+}
+
+#[test]
+fn test_missing_input() {
+    let loader = InputLoader::new("this/path/does/not/exist");
+    assert_eq!(loader.find(1), None);
+    let err = loader.load(1).unwrap_err().to_string();
+    assert!(err.contains("day1"), "{}", err);
+    assert!(err.contains(INPUT_DIR_VAR), "{}", err);
+}
@@ -0,0 +1,536 @@
+//! Working out what values the registers could have, without running the program.
+//!
+//! Each register is tracked as a range of values it might have before each instruction.
+//! Loops get their ranges widened to be unbounded so this always finishes,
+//! and then narrowed back down a bit, so the ranges can be bigger than they need to be,
+//! but never smaller.
+//! Overflowing and dividing by zero aren't treated as errors, apart from dividing by a literal 0.
+
+use std::{
+    collections::VecDeque,
+    fmt::{Display, Formatter},
+    ops::{Index, IndexMut},
+};
+
+use super::{
+    analysis::{jump_target, Patch},
+    Instruction, Operation, Register, REGISTER_COUNT,
+};
+
+/// How many times an instruction's state can grow before it gets widened
+const WIDEN_AFTER: usize = 3;
+/// How many times to go over everything again afterwards to win back some of what widening lost.
+/// It stops early if nothing changes.
+const NARROW_PASSES: usize = 10;
+
+/// A range of values, inclusive at both ends.
+///
+/// `i64::MIN` as the bottom and `i64::MAX` as the top mean there's no bound on that side.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Interval {
+    pub lo: i64,
+    pub hi: i64,
+}
+
+impl Interval {
+    /// Every value
+    pub const TOP: Interval = Interval {
+        lo: i64::MIN,
+        hi: i64::MAX,
+    };
+
+    /// Just the one value
+    pub fn exact(val: i64) -> Self {
+        Self { lo: val, hi: val }
+    }
+
+    pub fn contains(self, val: i64) -> bool {
+        self.lo <= val && val <= self.hi
+    }
+
+    /// The smallest interval containing both
+    pub fn join(self, other: Interval) -> Self {
+        Self {
+            lo: self.lo.min(other.lo),
+            hi: self.hi.max(other.hi),
+        }
+    }
+
+    /// Join, but any bound that grew becomes unbounded
+    fn widen(self, other: Interval) -> Self {
+        Self {
+            lo: if other.lo < self.lo {
+                i64::MIN
+            } else {
+                self.lo
+            },
+            hi: if other.hi > self.hi {
+                i64::MAX
+            } else {
+                self.hi
+            },
+        }
+    }
+
+    /// Apply a monotonic function to each end, keeping unbounded ends unbounded
+    /// and swapping the ends if `flips`.
+    fn map(self, flips: bool, f: impl Fn(i64) -> i64) -> Self {
+        let (lo_unbounded, hi_unbounded) = if flips {
+            (self.hi == i64::MAX, self.lo == i64::MIN)
+        } else {
+            (self.lo == i64::MIN, self.hi == i64::MAX)
+        };
+        let (lo, hi) = if flips {
+            (f(self.hi), f(self.lo))
+        } else {
+            (f(self.lo), f(self.hi))
+        };
+        Self {
+            lo: if lo_unbounded { i64::MIN } else { lo },
+            hi: if hi_unbounded { i64::MAX } else { hi },
+        }
+    }
+
+    fn add(self, n: i64) -> Self {
+        self.map(false, |val| val.saturating_add(n))
+    }
+
+    fn mul(self, n: i64) -> Self {
+        if n == 0 {
+            Interval::exact(0)
+        } else {
+            self.map(n < 0, |val| val.saturating_mul(n))
+        }
+    }
+
+    /// `n` can't be 0
+    fn div(self, n: i64) -> Self {
+        // rounding towards zero is still monotonic
+        self.map(n < 0, |val| val.checked_div(n).unwrap_or(i64::MAX))
+    }
+
+    /// `n` can't be 0
+    fn rem_euclid(self, n: i64) -> Self {
+        // the biggest remainder there can be, which for i64::MIN is i64::MAX
+        let max = (n.unsigned_abs() - 1) as i64;
+        if self.lo >= 0 && self.hi <= max {
+            self
+        } else {
+            Self { lo: 0, hi: max }
+        }
+    }
+
+    /// Keep only the values where `keep` is true.
+    /// `keep` has to be true for a range of values, like a comparison with 0.
+    fn filter(self, lo: i64, hi: i64) -> Option<Self> {
+        let out = Self {
+            lo: self.lo.max(lo),
+            hi: self.hi.min(hi),
+        };
+        if out.lo <= out.hi {
+            Some(out)
+        } else {
+            None
+        }
+    }
+
+    /// Everything in here but 0, if that's still a range
+    fn without_zero(self) -> Option<Self> {
+        if self == Interval::exact(0) {
+            None
+        } else if self.lo == 0 {
+            Some(Self { lo: 1, ..self })
+        } else if self.hi == 0 {
+            Some(Self { hi: -1, ..self })
+        } else {
+            Some(self)
+        }
+    }
+}
+
+impl Display for Interval {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.lo == self.hi {
+            return write!(f, "{}", self.lo);
+        }
+        match self.lo {
+            i64::MIN => write!(f, "(-inf")?,
+            lo => write!(f, "[{}", lo)?,
+        }
+        match self.hi {
+            i64::MAX => write!(f, ", +inf)"),
+            hi => write!(f, ", {}]", hi),
+        }
+    }
+}
+
+/// What each register might be.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct AbstractRegisters([Interval; REGISTER_COUNT]);
+
+impl AbstractRegisters {
+    /// Everything starts at 0
+    fn start() -> Self {
+        Self([Interval::exact(0); REGISTER_COUNT])
+    }
+
+    fn join(self, other: Self) -> Self {
+        let mut out = self;
+        for &reg in Register::ALL.iter() {
+            out[reg] = self[reg].join(other[reg]);
+        }
+        out
+    }
+
+    fn widen(self, other: Self) -> Self {
+        let mut out = self;
+        for &reg in Register::ALL.iter() {
+            out[reg] = self[reg].widen(other[reg]);
+        }
+        out
+    }
+}
+
+impl Index<Register> for AbstractRegisters {
+    type Output = Interval;
+    fn index(&self, reg: Register) -> &Interval {
+        &self.0[reg as usize]
+    }
+}
+
+impl IndexMut<Register> for AbstractRegisters {
+    fn index_mut(&mut self, reg: Register) -> &mut Interval {
+        &mut self.0[reg as usize]
+    }
+}
+
+/// Where an instruction might go, and what the registers might be when it gets there.
+/// A target of None means it jumps out of the program.
+fn transfer(
+    pc: usize,
+    instr: &Instruction,
+    regs: AbstractRegisters,
+    end: usize,
+) -> Vec<(Option<usize>, AbstractRegisters)> {
+    let argument = instr.argument;
+    let jump = jump_target(pc, argument, end);
+    let next = Some(pc + 1);
+    let with = |reg: Register, val: Interval| {
+        let mut regs = regs;
+        regs[reg] = val;
+        regs
+    };
+
+    match instr.operation {
+        Operation::Acc => vec![(next, with(Register::Acc, regs[Register::Acc].add(argument)))],
+        Operation::Jmp => vec![(jump, regs)],
+        Operation::Nop => vec![(next, regs)],
+
+        Operation::Set(reg) => vec![(next, with(reg, Interval::exact(argument)))],
+        Operation::Add(reg) => vec![(next, with(reg, regs[reg].add(argument)))],
+        Operation::Mul(reg) => vec![(next, with(reg, regs[reg].mul(argument)))],
+        // these always fail
+        Operation::Div(_) | Operation::Mod(_) if argument == 0 => vec![],
+        Operation::Div(reg) => vec![(next, with(reg, regs[reg].div(argument)))],
+        Operation::Mod(reg) => vec![(next, with(reg, regs[reg].rem_euclid(argument)))],
+        Operation::Cpy(dst, src) => vec![(next, with(dst, regs[src]))],
+
+        Operation::Jz(reg) | Operation::Jnz(reg) | Operation::Jgz(reg) | Operation::Jlz(reg) => {
+            let val = regs[reg];
+            let zero = val.filter(0, 0);
+            let (jumps, falls) = match instr.operation {
+                Operation::Jz(_) => (zero, val.without_zero()),
+                Operation::Jnz(_) => (val.without_zero(), zero),
+                Operation::Jgz(_) => (val.filter(1, i64::MAX), val.filter(i64::MIN, 0)),
+                _ => (val.filter(i64::MIN, -1), val.filter(0, i64::MAX)),
+            };
+            let mut out = Vec::new();
+            if let Some(val) = jumps {
+                out.push((jump, with(reg, val)));
+            }
+            if let Some(val) = falls {
+                out.push((next, with(reg, val)));
+            }
+            out
+        }
+
+        // could do anything to anything
+        Operation::Custom(_) => (0..=end)
+            .map(|target| {
+                (
+                    Some(target),
+                    AbstractRegisters([Interval::TOP; REGISTER_COUNT]),
+                )
+            })
+            .collect(),
+    }
+}
+
+/// What the abstract interpreter found out about a program.
+#[derive(Debug, Clone)]
+pub struct Report {
+    /// What the registers might be before each instruction, and at the end.
+    /// None if it can't get there.
+    pub states: Vec<Option<AbstractRegisters>>,
+    /// Instructions that can never run
+    pub dead: Vec<usize>,
+    /// Instructions that can run, but once they do the program can never end
+    /// (it loops forever or fails)
+    pub trapped: Vec<usize>,
+}
+
+impl Report {
+    /// What the accumulator might be when the program ends,
+    /// or None if it definitely never ends.
+    pub fn final_acc(&self) -> Option<Interval> {
+        // unwrap is ok because there's always an end state
+        self.states.last().unwrap().map(|regs| regs[Register::Acc])
+    }
+
+    /// Does the program definitely never end?
+    pub fn never_ends(&self) -> bool {
+        self.final_acc().is_none()
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.final_acc() {
+            Some(acc) => writeln!(f, "acc at the end: {}", acc)?,
+            None => writeln!(f, "never ends")?,
+        }
+        writeln!(f, "dead code: {:?}", self.dead)?;
+        write!(f, "can't end after: {:?}", self.trapped)
+    }
+}
+
+/// Interpret a program abstractly.
+pub fn interpret(program: &[Instruction]) -> Report {
+    let end = program.len();
+    let mut states: Vec<Option<AbstractRegisters>> = vec![None; end + 1];
+    let mut grown = vec![0; end + 1];
+
+    states[0] = Some(AbstractRegisters::start());
+    let mut todo = VecDeque::new();
+    // whether each pc is in `todo` already, so it doesn't go in twice
+    let mut queued = vec![false; end + 1];
+    todo.push_back(0);
+    queued[0] = true;
+    while let Some(pc) = todo.pop_front() {
+        queued[pc] = false;
+        if pc == end {
+            continue;
+        }
+        // unwrap is ok because only reachable pcs go in the queue
+        let regs = states[pc].unwrap();
+        for (target, out) in transfer(pc, &program[pc], regs, end) {
+            let target = match target {
+                Some(it) => it,
+                None => continue,
+            };
+            let new = match states[target] {
+                None => out,
+                Some(old) => {
+                    let joined = old.join(out);
+                    if joined == old {
+                        continue;
+                    }
+                    grown[target] += 1;
+                    if grown[target] > WIDEN_AFTER {
+                        old.widen(joined)
+                    } else {
+                        joined
+                    }
+                }
+            };
+            states[target] = Some(new);
+            if !queued[target] {
+                queued[target] = true;
+                todo.push_back(target);
+            }
+        }
+    }
+
+    // Everything is at least as big as it needs to be now,
+    // so working out each state from the ones before it again can only shrink it.
+    for _ in 0..NARROW_PASSES {
+        let mut narrowed: Vec<Option<AbstractRegisters>> = vec![None; end + 1];
+        narrowed[0] = Some(AbstractRegisters::start());
+        for (pc, regs) in states.iter().enumerate().take(end) {
+            let regs = match regs {
+                Some(it) => *it,
+                None => continue,
+            };
+            for (target, out) in transfer(pc, &program[pc], regs, end) {
+                if let Some(target) = target {
+                    narrowed[target] = Some(match narrowed[target] {
+                        Some(old) => old.join(out),
+                        None => out,
+                    });
+                }
+            }
+        }
+        if narrowed == states {
+            break;
+        }
+        states = narrowed;
+    }
+
+    // which edges can be taken, so we can tell what can reach the end
+    let mut predecessors = vec![Vec::new(); end + 1];
+    for (pc, regs) in states.iter().enumerate().take(end) {
+        if let Some(regs) = regs {
+            for (target, _) in transfer(pc, &program[pc], *regs, end) {
+                if let Some(target) = target {
+                    predecessors[target].push(pc);
+                }
+            }
+        }
+    }
+
+    // what can get to the end, going backwards along edges that can be taken
+    let mut reaches_end = vec![false; end + 1];
+    reaches_end[end] = true;
+    let mut todo = vec![end];
+    while let Some(pc) = todo.pop() {
+        for &prev in predecessors[pc].iter() {
+            if !reaches_end[prev] {
+                reaches_end[prev] = true;
+                todo.push(prev);
+            }
+        }
+    }
+
+    let dead = (0..end).filter(|&pc| states[pc].is_none()).collect();
+    let trapped = (0..end)
+        .filter(|&pc| states[pc].is_some() && !reaches_end[pc])
+        .collect();
+    Report {
+        states,
+        dead,
+        trapped,
+    }
+}
+
+/// What happens to the accumulator under one patch.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PatchOutcome {
+    pub patch: Patch,
+    /// What the accumulator might be at the end, or None if it never ends
+    pub final_acc: Option<Interval>,
+}
+
+/// What happens under every `jmp`/`nop` swap.
+#[derive(Debug, Clone)]
+pub struct PatchBounds {
+    /// The result for each patch, in program order
+    pub outcomes: Vec<PatchOutcome>,
+}
+
+impl PatchBounds {
+    /// What the accumulator might be at the end under any patch that might end,
+    /// or None if no patch ends.
+    pub fn final_acc(&self) -> Option<Interval> {
+        self.outcomes
+            .iter()
+            .filter_map(|outcome| outcome.final_acc)
+            .reduce(Interval::join)
+    }
+
+    /// The patches that might make the program end
+    pub fn ending(&self) -> impl Iterator<Item = &PatchOutcome> + '_ {
+        self.outcomes
+            .iter()
+            .filter(|outcome| outcome.final_acc.is_some())
+    }
+}
+
+/// Work out what the accumulator could end up as under each `jmp`/`nop` swap.
+///
+/// Swapping a dead instruction can't change anything, so those just get the
+/// unpatched result and only the live ones are interpreted again.
+pub fn patch_bounds(program: &[Instruction]) -> PatchBounds {
+    let unpatched = interpret(program);
+    let mut patched_program = program.to_vec();
+    let outcomes = program
+        .iter()
+        .enumerate()
+        .filter_map(|(pc, instr)| Patch::swap_jmp_nop(pc, instr))
+        .map(|patch| {
+            let final_acc = if unpatched.states[patch.pc].is_none() {
+                unpatched.final_acc()
+            } else {
+                patch.apply(&mut patched_program);
+                let report = interpret(&patched_program);
+                patched_program[patch.pc] = patch.original.clone();
+                report.final_acc()
+            };
+            PatchOutcome { patch, final_acc }
+        })
+        .collect();
+    PatchBounds { outcomes }
+}
+
+#[test]
+fn test_interpret() -> anyhow::Result<()> {
+    // the program from day 8
+    let program = super::Executor::parse_program(
+        r"nop +0
+acc +1
+jmp +4
+acc +3
+jmp -3
+acc -99
+acc +1
+jmp -4
+acc +6",
+    )?;
+    let report = interpret(&program);
+    assert!(report.never_ends());
+    assert_eq!(report.dead, vec![5, 8]);
+    assert_eq!(report.trapped, vec![0, 1, 2, 3, 4, 6, 7]);
+
+    let bounds = patch_bounds(&program);
+    let ending = bounds
+        .ending()
+        .map(|outcome| (outcome.patch.pc, outcome.final_acc))
+        .collect::<Vec<_>>();
+    assert_eq!(ending, vec![(7, Some(Interval::exact(8)))]);
+    assert_eq!(bounds.final_acc(), Some(Interval::exact(8)));
+
+    // a countdown loop: acc is unbounded because of widening, but a is 0 at the end
+    let program = super::Executor::parse_program(
+        r"set a +10
+acc +3
+add a -1
+jgz a -2
+jmp +2
+acc +1
+nop +0",
+    )?;
+    let report = interpret(&program);
+    assert_eq!(report.dead, vec![5]);
+    assert!(report.trapped.is_empty());
+    let at_end = report.states[program.len()].unwrap();
+    assert_eq!(at_end[Register::A], Interval::exact(0));
+    assert_eq!(report.final_acc().unwrap().lo, 3);
+
+    let small = Interval { lo: -5, hi: 5 };
+    assert_eq!(small.rem_euclid(-3), Interval { lo: 0, hi: 2 });
+    assert_eq!(
+        Interval { lo: 1, hi: 2 }.rem_euclid(3),
+        Interval { lo: 1, hi: 2 }
+    );
+    // -1 mod i64::MIN is i64::MAX, so that has to be in there
+    let huge = small.rem_euclid(i64::MIN);
+    assert_eq!(
+        huge,
+        Interval {
+            lo: 0,
+            hi: i64::MAX
+        }
+    );
+    assert!(huge.contains((-1i64).rem_euclid(i64::MIN)));
+
+    Ok(())
+}
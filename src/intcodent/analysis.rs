@@ -0,0 +1,380 @@
+//! Working out where a program can go without running it.
+//!
+//! This only looks at control flow, so it assumes no instruction fails at runtime
+//! (by overflowing or dividing by zero, say).
+
+use anyhow::{bail, Result};
+
+use std::{
+    collections::VecDeque,
+    fmt::{Display, Formatter},
+};
+
+use super::{Instruction, Operation};
+
+/// Where each instruction can go next.
+///
+/// The nodes are the indices of the instructions, plus one more node for the end of the program
+/// (the index just past the last instruction).
+#[derive(Debug, Clone)]
+pub struct ControlFlowGraph {
+    successors: Vec<Vec<usize>>,
+    predecessors: Vec<Vec<usize>>,
+    /// Instructions that could go anywhere (custom operations)
+    unknown: Vec<usize>,
+    /// Instructions that jump outside the program, and would fail if run
+    out_of_bounds: Vec<usize>,
+}
+
+impl ControlFlowGraph {
+    pub fn new(program: &[Instruction]) -> Self {
+        let end = program.len();
+        let mut successors = vec![Vec::new(); end + 1];
+        let mut predecessors = vec![Vec::new(); end + 1];
+        let mut unknown = Vec::new();
+        let mut out_of_bounds = Vec::new();
+
+        for (pc, instr) in program.iter().enumerate() {
+            let jump = jump_target(pc, instr.argument, end);
+            let next = match instr.operation {
+                Operation::Custom(_) => {
+                    unknown.push(pc);
+                    continue;
+                }
+                Operation::Jmp => vec![jump],
+                op if op.condition().is_some() => vec![Some(pc + 1), jump],
+                _ => vec![Some(pc + 1)],
+            };
+            for target in next {
+                match target {
+                    Some(target) => {
+                        if !successors[pc].contains(&target) {
+                            successors[pc].push(target);
+                            predecessors[target].push(pc);
+                        }
+                    }
+                    None => out_of_bounds.push(pc),
+                }
+            }
+        }
+
+        Self {
+            successors,
+            predecessors,
+            unknown,
+            out_of_bounds,
+        }
+    }
+
+    /// The node for the end of the program
+    pub fn end(&self) -> usize {
+        self.successors.len() - 1
+    }
+
+    /// Where the instruction at `pc` can go next.
+    /// Empty for the end, and for custom operations, which could go anywhere.
+    pub fn successors(&self, pc: usize) -> &[usize] {
+        &self.successors[pc]
+    }
+
+    /// Which instructions can go to `pc` next.
+    pub fn predecessors(&self, pc: usize) -> &[usize] {
+        &self.predecessors[pc]
+    }
+
+    /// Instructions that are custom operations, so where they go can't be known
+    pub fn unknown(&self) -> &[usize] {
+        &self.unknown
+    }
+
+    /// Instructions with a jump that leaves the program
+    pub fn out_of_bounds(&self) -> &[usize] {
+        &self.out_of_bounds
+    }
+
+    /// Does every instruction have exactly one place it can go
+    /// (or none, if it jumps out of bounds)?
+    pub fn is_deterministic(&self) -> bool {
+        self.unknown.is_empty() && self.successors.iter().all(|next| next.len() <= 1)
+    }
+
+    /// For each node, can it get to the end of the program?
+    ///
+    /// Custom operations are assumed to be able to.
+    /// In a deterministic program, true means it definitely does.
+    pub fn reaches_end(&self) -> Vec<bool> {
+        let starts = std::iter::once(self.end()).chain(self.unknown.iter().copied());
+        self.search(starts, &self.predecessors)
+    }
+
+    /// For each node, can it be reached from the start of the program?
+    ///
+    /// Everything after a custom operation is assumed to be reachable if it is.
+    pub fn reachable(&self) -> Vec<bool> {
+        let mut reachable = self.search(std::iter::once(0), &self.successors);
+        if self.unknown.iter().any(|&pc| reachable[pc]) {
+            reachable = vec![true; self.successors.len()];
+        }
+        reachable
+    }
+
+    /// Breadth-first search from the starts along the edges
+    fn search(&self, starts: impl Iterator<Item = usize>, edges: &[Vec<usize>]) -> Vec<bool> {
+        let mut seen = vec![false; edges.len()];
+        let mut todo = VecDeque::new();
+        for start in starts {
+            if start < seen.len() && !seen[start] {
+                seen[start] = true;
+                todo.push_back(start);
+            }
+        }
+        while let Some(node) = todo.pop_front() {
+            for &next in edges[node].iter() {
+                if !seen[next] {
+                    seen[next] = true;
+                    todo.push_back(next);
+                }
+            }
+        }
+        seen
+    }
+
+    /// Follow the only path from the start until it ends, leaves the program, or loops.
+    /// Returns the instructions on it in order, and how it stopped.
+    ///
+    /// Only makes sense for deterministic programs.
+    fn path(&self) -> (Vec<usize>, PathEnd) {
+        let mut on_path = vec![false; self.successors.len()];
+        let mut path = Vec::new();
+        let mut pc = 0;
+        loop {
+            if pc == self.end() {
+                return (path, PathEnd::Ended);
+            }
+            if on_path[pc] {
+                return (path, PathEnd::Looped);
+            }
+            on_path[pc] = true;
+            path.push(pc);
+            match self.successors[pc].first() {
+                Some(&next) => pc = next,
+                None => return (path, PathEnd::OutOfBounds),
+            }
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum PathEnd {
+    Ended,
+    Looped,
+    OutOfBounds,
+}
+
+/// Where a jump from `pc` by `offset` lands, or None if it leaves the program.
+/// Landing just past the end is fine.
+pub(super) fn jump_target(pc: usize, offset: i64, end: usize) -> Option<usize> {
+    match (pc as i64).checked_add(offset) {
+        Some(target) if target >= 0 && target as usize <= end => Some(target as usize),
+        _ => None,
+    }
+}
+
+/// Swapping the operation of one instruction.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Patch {
+    pub pc: usize,
+    pub original: Instruction,
+    pub patched: Instruction,
+}
+
+impl Patch {
+    /// Swap a `jmp` for a `nop` or the other way around, keeping the argument.
+    /// None if the instruction is anything else.
+    pub fn swap_jmp_nop(pc: usize, original: &Instruction) -> Option<Self> {
+        let operation = match original.operation {
+            Operation::Jmp => Operation::Nop,
+            Operation::Nop => Operation::Jmp,
+            _ => return None,
+        };
+        Some(Self {
+            pc,
+            original: original.clone(),
+            patched: Instruction {
+                operation,
+                argument: original.argument,
+            },
+        })
+    }
+
+    /// Apply this to a program.
+    pub fn apply(&self, program: &mut [Instruction]) {
+        program[self.pc] = self.patched.clone();
+    }
+}
+
+impl Display for Patch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} -> {}", self.pc, self.original, self.patched)
+    }
+}
+
+/// What a patch would do.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Verdict {
+    /// It's run before the program loops, and afterwards it goes here, which reaches the end
+    Fixes { target: usize },
+    /// It's never run, so changing it changes nothing
+    NotExecuted,
+    /// It's run, but afterwards it goes here, which never reaches the end
+    StillLoops { target: usize },
+    /// It's run, but afterwards it jumps out of the program
+    OutOfBounds,
+}
+
+/// A patch that could be applied and what it would do.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Candidate {
+    pub patch: Patch,
+    pub verdict: Verdict,
+}
+
+impl Display for Candidate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: ", self.patch)?;
+        match self.verdict {
+            Verdict::Fixes { target } => write!(
+                f,
+                "fixes it: it runs before the loop, then goes to {}, which reaches the end",
+                target
+            ),
+            Verdict::NotExecuted => write!(f, "does nothing: it never runs"),
+            Verdict::StillLoops { target } => write!(
+                f,
+                "still loops: it goes to {}, which never reaches the end",
+                target
+            ),
+            Verdict::OutOfBounds => write!(f, "breaks it: it jumps out of the program"),
+        }
+    }
+}
+
+/// Check every `jmp`/`nop` swap to see whether it would make a looping program end.
+///
+/// The patches are only worked out for deterministic programs
+/// (so no conditional jumps or custom operations). This takes linear time.
+pub fn patch_candidates(program: &[Instruction]) -> Result<Vec<Candidate>> {
+    analyze(program).map(|(candidates, _)| candidates)
+}
+
+/// Get the patch candidates, and the path the unpatched program takes
+fn analyze(program: &[Instruction]) -> Result<(Vec<Candidate>, Vec<usize>)> {
+    let cfg = ControlFlowGraph::new(program);
+    if !cfg.is_deterministic() {
+        bail!("can't work out patches for a program with conditional jumps or custom operations");
+    }
+    let (path, how) = cfg.path();
+    if how == PathEnd::Ended {
+        bail!("the program already ends");
+    }
+
+    let mut on_path = vec![false; program.len()];
+    for &pc in path.iter() {
+        on_path[pc] = true;
+    }
+    let reaches_end = cfg.reaches_end();
+
+    // Changing an instruction on the path doesn't change how we got to it,
+    // and it can't be on the way from its new target to the end
+    // (because if it was, it would reach the end already),
+    // so all that matters is where it goes next.
+    let candidates = program
+        .iter()
+        .enumerate()
+        .filter_map(|(pc, instr)| Patch::swap_jmp_nop(pc, instr))
+        .map(|patch| {
+            let verdict = if !on_path[patch.pc] {
+                Verdict::NotExecuted
+            } else {
+                let target = match patch.patched.operation {
+                    Operation::Jmp => jump_target(patch.pc, patch.patched.argument, program.len()),
+                    _ => Some(patch.pc + 1),
+                };
+                match target {
+                    None => Verdict::OutOfBounds,
+                    Some(target) if reaches_end[target] => Verdict::Fixes { target },
+                    Some(target) => Verdict::StillLoops { target },
+                }
+            };
+            Candidate { patch, verdict }
+        })
+        .collect();
+    Ok((candidates, path))
+}
+
+/// Find the `jmp`/`nop` swap that makes a looping program end, if there is one.
+/// If several would, this is the first one the program runs.
+///
+/// See [`patch_candidates`] for which programs this works on.
+pub fn find_repair(program: &[Instruction]) -> Result<Option<Patch>> {
+    let (candidates, path) = analyze(program)?;
+    // which candidate fixes it from each pc, so the path only has to be walked once
+    let mut fixes = vec![None; program.len()];
+    for (idx, cand) in candidates.iter().enumerate() {
+        if matches!(cand.verdict, Verdict::Fixes { .. }) {
+            fixes[cand.patch.pc] = Some(idx);
+        }
+    }
+    let fix = path.into_iter().find_map(|pc| fixes[pc]);
+    Ok(fix.map(|idx| candidates[idx].patch.clone()))
+}
+
+#[test]
+fn test_repair() -> Result<()> {
+    let program = super::Executor::parse_program(
+        r"nop +0
+acc +1
+jmp +4
+acc +3
+jmp -3
+acc -99
+acc +1
+jmp -4
+acc +6",
+    )?;
+
+    let cfg = ControlFlowGraph::new(&program);
+    assert!(cfg.is_deterministic());
+    assert_eq!(cfg.successors(2), &[6]);
+    assert_eq!(cfg.predecessors(1), &[0, 4]);
+    let reaches_end = cfg.reaches_end();
+    assert_eq!(
+        reaches_end,
+        vec![false, false, false, false, false, false, false, false, true, true]
+    );
+
+    let candidates = patch_candidates(&program)?;
+    let verdicts = candidates
+        .iter()
+        .map(|cand| (cand.patch.pc, cand.verdict))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        verdicts,
+        vec![
+            (0, Verdict::StillLoops { target: 0 }),
+            (2, Verdict::StillLoops { target: 3 }),
+            (4, Verdict::StillLoops { target: 5 }),
+            (7, Verdict::Fixes { target: 8 }),
+        ]
+    );
+
+    let patch = find_repair(&program)?.unwrap();
+    assert_eq!(patch.pc, 7);
+    let mut program = program;
+    patch.apply(&mut program);
+    let mut exe = super::Executor::new_from_program(program.clone());
+    assert_eq!(exe.run_until_end_or_loop()?, super::Termination::Ended(8));
+    assert!(patch_candidates(&program).is_err());
+
+    Ok(())
+}
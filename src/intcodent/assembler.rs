@@ -0,0 +1,329 @@
+//! Turning text into programs and back.
+//!
+//! Programs are one instruction per line, like `jmp -3` or `add b +7`. On top of that:
+//!
+//! ```text
+//! # comments start with `#` or `;` and go to the end of the line
+//! start:          ; a label names the index of the next instruction
+//!     acc +1
+//!     jnz a start ; jumps can go to a label instead of an offset
+//! end: nop +0     ; labels can go on the same line as an instruction
+//! ```
+//!
+//! A label after the last instruction names the end of the program, so jumping to it stops the program.
+
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::{Display, Formatter},
+};
+
+use super::{Instruction, InstructionSet, Register};
+
+/// Something wrong with a program, and where it is.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AsmError {
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based column of the first character that's wrong
+    pub column: usize,
+    /// How many characters are wrong
+    pub len: usize,
+    pub message: String,
+    /// The line the error is on
+    pub source: String,
+}
+
+impl Display for AsmError {
+    /// Writes the error with the line it's on, underlining the bad part.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )?;
+        // tabs would throw the underline off
+        writeln!(f, "  {}", self.source.replace('\t', " "))?;
+        write!(
+            f,
+            "  {}{}",
+            " ".repeat(self.column - 1),
+            "^".repeat(self.len.max(1))
+        )
+    }
+}
+
+impl Error for AsmError {}
+
+/// A bit of a line and where it is
+#[derive(Debug, Copy, Clone)]
+struct Token<'a> {
+    text: &'a str,
+    /// 0-based, in characters
+    column: usize,
+}
+
+impl Token<'_> {
+    fn len(&self) -> usize {
+        self.text.chars().count()
+    }
+}
+
+/// An instruction whose arguments haven't been worked out yet
+struct Pending<'a> {
+    /// 0-based
+    line: usize,
+    mnemonic: Token<'a>,
+    registers: Vec<Register>,
+    /// The argument, if it takes one
+    argument: Option<Token<'a>>,
+}
+
+/// Split a line into tokens, ignoring any comment.
+/// Also returns how many characters there are before the comment.
+fn tokenize(line: &str) -> (Vec<Token<'_>>, usize) {
+    let code = match line.find(['#', ';']) {
+        Some(comment) => &line[..comment],
+        None => line,
+    };
+
+    let mut tokens = Vec::new();
+    // column and byte index of where the current token started
+    let mut start = None;
+    let mut columns = 0;
+    for (column, (idx, c)) in code.char_indices().enumerate() {
+        if c.is_whitespace() {
+            if let Some((start_column, start_idx)) = start.take() {
+                tokens.push(Token {
+                    text: &code[start_idx..idx],
+                    column: start_column,
+                });
+            }
+        } else if start.is_none() {
+            start = Some((column, idx));
+        }
+        columns = column + 1;
+    }
+    if let Some((start_column, start_idx)) = start {
+        tokens.push(Token {
+            text: &code[start_idx..],
+            column: start_column,
+        });
+    }
+    (tokens, columns)
+}
+
+/// Is this ok as the name of a label?
+fn is_label_name(s: &str) -> bool {
+    let mut chars = s.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Assemble a program, using the opcodes in the instruction set.
+pub fn assemble(source: &str, set: &InstructionSet) -> Result<Vec<Instruction>, AsmError> {
+    let lines = source.lines().collect::<Vec<_>>();
+    let error = |line: usize, column: usize, len: usize, message: String| AsmError {
+        line: line + 1,
+        column: column + 1,
+        len,
+        message,
+        source: lines[line].to_string(),
+    };
+    let token_error = |line: usize, token: Token, message: String| {
+        error(line, token.column, token.len(), message)
+    };
+
+    // First find all the labels and split up the instructions
+    let mut labels = HashMap::new();
+    let mut pending = Vec::new();
+    for (line_idx, line) in lines.iter().enumerate() {
+        let (tokens, columns) = tokenize(line);
+        let mut tokens = tokens.as_slice();
+
+        while let Some((first, rest)) = tokens.split_first() {
+            let name = match first.text.strip_suffix(':') {
+                Some(it) => it,
+                None => break,
+            };
+            if !is_label_name(name) {
+                return Err(token_error(
+                    line_idx,
+                    *first,
+                    format!("`{}` isn't a valid label name", name),
+                ));
+            }
+            if labels.insert(name, pending.len()).is_some() {
+                return Err(token_error(
+                    line_idx,
+                    *first,
+                    format!("the label `{}` is already defined", name),
+                ));
+            }
+            tokens = rest;
+        }
+
+        if let Some((mnemonic, operands)) = tokens.split_first() {
+            // check the shape now so errors come out in order
+            let (registers, takes_argument) = match set.signature(mnemonic.text) {
+                Some(it) => it,
+                None => {
+                    return Err(token_error(
+                        line_idx,
+                        *mnemonic,
+                        format!("unknown opcode `{}`", mnemonic.text),
+                    ))
+                }
+            };
+            let expected = registers + takes_argument as usize;
+            if operands.len() > expected {
+                return Err(token_error(
+                    line_idx,
+                    operands[expected],
+                    format!(
+                        "`{}` expects {} operand(s), found {}",
+                        mnemonic.text,
+                        expected,
+                        operands.len()
+                    ),
+                ));
+            }
+            if operands.len() < expected {
+                return Err(error(
+                    line_idx,
+                    columns,
+                    1,
+                    format!(
+                        "`{}` expects {} operand(s), found {}",
+                        mnemonic.text,
+                        expected,
+                        operands.len()
+                    ),
+                ));
+            }
+
+            let (regs, argument) = operands.split_at(registers);
+            let registers = regs
+                .iter()
+                .map(|&tok| {
+                    tok.text
+                        .parse::<Register>()
+                        .map_err(|e| token_error(line_idx, tok, e.to_string()))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            pending.push(Pending {
+                line: line_idx,
+                mnemonic: *mnemonic,
+                registers,
+                argument: argument.first().copied(),
+            });
+        }
+    }
+
+    // Then fill in the arguments
+    pending
+        .iter()
+        .enumerate()
+        .map(|(pc, instr)| {
+            let mut built = set
+                .build(instr.mnemonic.text, &instr.registers, 0)
+                .map_err(|e| token_error(instr.line, instr.mnemonic, e.to_string()))?;
+            if let Some(tok) = instr.argument {
+                built.argument = if tok
+                    .text
+                    .starts_with(|c: char| c == '+' || c == '-' || c.is_ascii_digit())
+                {
+                    tok.text
+                        .parse()
+                        .map_err(|e| token_error(instr.line, tok, format!("bad argument: {}", e)))?
+                } else if !built.operation.is_jump() {
+                    return Err(token_error(
+                        instr.line,
+                        tok,
+                        format!("only jumps can use labels, not `{}`", instr.mnemonic.text),
+                    ));
+                } else {
+                    match labels.get(tok.text) {
+                        Some(&target) => target as i64 - pc as i64,
+                        None => {
+                            return Err(token_error(
+                                instr.line,
+                                tok,
+                                format!("there's no label called `{}`", tok.text),
+                            ))
+                        }
+                    }
+                };
+            }
+            Ok(built)
+        })
+        .collect()
+}
+
+/// Write a program out with one instruction per line, in the form [`assemble`] reads.
+///
+/// Jumps are written as offsets, so the output is the same however the program was written.
+pub fn disassemble(program: &[Instruction]) -> String {
+    let mut out = String::new();
+    for instr in program {
+        out.push_str(&instr.to_string());
+        out.push('\n');
+    }
+    out
+}
+
+#[test]
+fn test_assemble() -> Result<(), Box<dyn Error>> {
+    let set = InstructionSet::new();
+    let program = assemble(
+        r"# counts down from 3
+    set a +3
+loop:   acc +2 ; the body
+    add a -1
+    jnz a loop
+    jmp end
+    acc -99
+end:",
+        &set,
+    )?;
+    let text = disassemble(&program);
+    assert_eq!(
+        text,
+        "set a +3\nacc +2\nadd a -1\njnz a -2\njmp +2\nacc -99\n"
+    );
+    assert_eq!(assemble(&text, &set)?, program);
+
+    let mut exe = super::Executor::new_from_program(program);
+    assert_eq!(exe.run_until_end()?, 6);
+
+    Ok(())
+}
+
+#[test]
+fn test_errors() {
+    let set = InstructionSet::new();
+    let err = |src: &str| assemble(src, &set).unwrap_err();
+
+    let e = err("nop +0\n  jmp  nowhere # oops");
+    assert_eq!((e.line, e.column, e.len), (2, 8, 7));
+    assert_eq!(e.message, "there's no label called `nowhere`");
+    assert_eq!(
+        e.to_string(),
+        "line 2, column 8: there's no label called `nowhere`\n    jmp  nowhere # oops\n         ^^^^^^^"
+    );
+
+    let e = err("acc +1\nfrob +2");
+    assert_eq!((e.line, e.column, e.len), (2, 1, 4));
+    let e = err("set q +2");
+    assert_eq!((e.line, e.column, e.len), (1, 5, 1));
+    let e = err("acc +1 +2");
+    assert_eq!((e.line, e.column, e.len), (1, 8, 2));
+    let e = err("add a  ; forgot it");
+    assert_eq!((e.line, e.column), (1, 8));
+    let e = err("acc 1x");
+    assert_eq!((e.line, e.column, e.len), (1, 5, 2));
+    let e = err("x: nop +0\nx: nop +0");
+    assert_eq!((e.line, e.column, e.len), (2, 1, 2));
+    let e = err("x: acc x");
+    assert_eq!(e.message, "only jumps can use labels, not `acc`");
+}
@@ -0,0 +1,602 @@
+//! Tracing, breakpoints and watchpoints for an [`Executor`].
+//!
+//! [`Debugger::interactive`] reads commands one line at a time; they're listed in [`HELP`].
+
+use anyhow::{anyhow, bail, Context, Error, Result};
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt::{Display, Formatter},
+    io::{BufRead, Write},
+    str::FromStr,
+};
+
+use super::{ExecError, Executor, Instruction, Register, Registers};
+
+/// How many instructions [`Debugger::run`] executes before giving up, unless it's changed
+/// with [`Debugger::set_run_limit`].
+pub const DEFAULT_RUN_LIMIT: usize = 100_000;
+
+/// The commands [`Debugger::interactive`] understands
+pub const HELP: &str = "s [N]          step N instructions (default 1)
+c              continue until something stops it
+b PC           toggle a breakpoint before the instruction at PC
+w REG [OP N]   watch a register; OP is one of == != < > (default: any change)
+d N            delete watchpoint N
+r              print the registers
+l              list the program around the pc
+t [N]          print the last N trace entries (default 10)
+u [N]          undo the last N instructions (default 1)
+h              print this
+q              quit";
+
+/// One executed instruction and the state around it.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TraceEntry {
+    /// Where the instruction was
+    pub pc: usize,
+    pub instruction: Instruction,
+    pub before: Registers,
+    pub after: Registers,
+    /// Where execution went next
+    pub next_pc: usize,
+}
+
+impl Display for TraceEntry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:>4}: {:<12} ", self.pc, self.instruction.to_string())?;
+        let mut changed = false;
+        for &reg in Register::ALL.iter() {
+            if self.before[reg] != self.after[reg] {
+                write!(f, " {} {} -> {}", reg, self.before[reg], self.after[reg])?;
+                changed = true;
+            }
+        }
+        if !changed {
+            write!(f, " (no change)")?;
+        }
+        if self.next_pc != self.pc + 1 {
+            write!(f, " => {}", self.next_pc)?;
+        }
+        Ok(())
+    }
+}
+
+/// When a watchpoint fires.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum WatchCondition {
+    /// Whenever the register changes
+    Changed,
+    /// When the register becomes this
+    Equals(i64),
+    NotEquals(i64),
+    /// When the register becomes less than this
+    Below(i64),
+    /// When the register becomes greater than this
+    Above(i64),
+}
+
+impl WatchCondition {
+    /// Does this fire when a register goes from `old` to `new`?
+    ///
+    /// Apart from `Changed`, these only fire when the condition starts being true,
+    /// so a watchpoint doesn't go off on every step after it's reached.
+    pub fn fires(self, old: i64, new: i64) -> bool {
+        let holds = |val: i64| match self {
+            WatchCondition::Changed => false,
+            WatchCondition::Equals(it) => val == it,
+            WatchCondition::NotEquals(it) => val != it,
+            WatchCondition::Below(it) => val < it,
+            WatchCondition::Above(it) => val > it,
+        };
+        match self {
+            WatchCondition::Changed => old != new,
+            _ => holds(new) && !holds(old),
+        }
+    }
+}
+
+/// Stop when a register meets a condition.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Watchpoint {
+    pub register: Register,
+    pub condition: WatchCondition,
+}
+
+impl Watchpoint {
+    /// Watch the accumulator for any change
+    pub fn accumulator() -> Self {
+        Self {
+            register: Register::Acc,
+            condition: WatchCondition::Changed,
+        }
+    }
+}
+
+impl Display for Watchpoint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.condition {
+            WatchCondition::Changed => write!(f, "{} changes", self.register),
+            WatchCondition::Equals(it) => write!(f, "{} == {}", self.register, it),
+            WatchCondition::NotEquals(it) => write!(f, "{} != {}", self.register, it),
+            WatchCondition::Below(it) => write!(f, "{} < {}", self.register, it),
+            WatchCondition::Above(it) => write!(f, "{} > {}", self.register, it),
+        }
+    }
+}
+
+impl FromStr for Watchpoint {
+    type Err = Error;
+    /// Parse `acc`, or something like `acc > 5`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let split = s.split_ascii_whitespace().collect::<Vec<_>>();
+        let (register, condition) = match split.as_slice() {
+            [reg] => (reg.parse()?, WatchCondition::Changed),
+            [reg, op, val] => {
+                let val = val
+                    .parse()
+                    .with_context(|| format!("bad value `{}`", val))?;
+                let condition = match *op {
+                    "==" => WatchCondition::Equals(val),
+                    "!=" => WatchCondition::NotEquals(val),
+                    "<" => WatchCondition::Below(val),
+                    ">" => WatchCondition::Above(val),
+                    oh_no => bail!("unknown comparison `{}`", oh_no),
+                };
+                (reg.parse()?, condition)
+            }
+            _ => bail!("expected `REG` or `REG OP VALUE`"),
+        };
+        Ok(Self {
+            register,
+            condition,
+        })
+    }
+}
+
+/// Why the debugger stopped running.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Stop {
+    /// Ran as many steps as it was asked to
+    Stepped,
+    /// About to execute an instruction with a breakpoint on it
+    Breakpoint(usize),
+    /// A watchpoint fired; this is its index
+    Watchpoint { index: usize, old: i64, new: i64 },
+    /// About to execute an instruction a second time, like
+    /// [`Executor::run_until_end_or_loop`] checks for.
+    /// This only stops the debugger the first time it happens.
+    Revisit(usize),
+    /// [`Debugger::run`] executed this many instructions without anything else stopping it,
+    /// which probably means it's looping forever
+    Limit(usize),
+    /// The pc went past the end of the program
+    End,
+    /// The next instruction couldn't be executed
+    Error(ExecError),
+}
+
+impl Display for Stop {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Stop::Stepped => write!(f, "stepped"),
+            Stop::Breakpoint(pc) => write!(f, "hit breakpoint at {}", pc),
+            Stop::Watchpoint { index, old, new } => {
+                write!(f, "watchpoint {} fired: {} -> {}", index, old, new)
+            }
+            Stop::Revisit(pc) => write!(f, "about to run {} a second time", pc),
+            Stop::Limit(count) => write!(f, "gave up after running {} instructions", count),
+            Stop::End => write!(f, "program ended"),
+            Stop::Error(e) => write!(f, "error: {}", e),
+        }
+    }
+}
+
+/// Wraps an [`Executor`] so it can be stopped partway through.
+///
+/// Tracing and history are turned on for the executor it wraps.
+pub struct Debugger {
+    exe: Executor,
+    breakpoints: BTreeSet<usize>,
+    /// Deleted watchpoints are None so the indices of the others don't change
+    watchpoints: Vec<Option<Watchpoint>>,
+    /// How many times each pc has been executed
+    visits: HashMap<usize, usize>,
+    /// How many instructions had been executed when it stopped for a revisit, if it has
+    revisited_at: Option<usize>,
+    /// Whether it's tried to execute anything yet.
+    /// Breakpoints are checked after each step, so the first instruction needs checking on its own.
+    started: bool,
+    run_limit: usize,
+}
+
+impl Debugger {
+    pub fn new(mut exe: Executor) -> Self {
+        exe.enable_trace();
+        exe.enable_history();
+        Self {
+            exe,
+            breakpoints: BTreeSet::new(),
+            watchpoints: Vec::new(),
+            visits: HashMap::new(),
+            revisited_at: None,
+            started: false,
+            run_limit: DEFAULT_RUN_LIMIT,
+        }
+    }
+
+    /// Get the executor being debugged.
+    pub fn executor(&self) -> &Executor {
+        &self.exe
+    }
+
+    /// Stop being a debugger.
+    pub fn into_executor(self) -> Executor {
+        self.exe
+    }
+
+    /// Stop before executing the instruction at this pc.
+    pub fn add_breakpoint(&mut self, pc: usize) {
+        self.breakpoints.insert(pc);
+    }
+
+    /// Returns whether there was a breakpoint there.
+    pub fn remove_breakpoint(&mut self, pc: usize) -> bool {
+        self.breakpoints.remove(&pc)
+    }
+
+    pub fn breakpoints(&self) -> impl Iterator<Item = usize> + '_ {
+        self.breakpoints.iter().copied()
+    }
+
+    /// Add a watchpoint and return its index.
+    pub fn add_watchpoint(&mut self, watch: Watchpoint) -> usize {
+        self.watchpoints.push(Some(watch));
+        self.watchpoints.len() - 1
+    }
+
+    /// Returns the watchpoint if there was one at that index.
+    pub fn remove_watchpoint(&mut self, index: usize) -> Option<Watchpoint> {
+        self.watchpoints.get_mut(index).and_then(Option::take)
+    }
+
+    /// Change how many instructions `run` executes before giving up.
+    pub fn set_run_limit(&mut self, limit: usize) {
+        self.run_limit = limit;
+    }
+
+    /// Iterate over the watchpoints and their indices.
+    pub fn watchpoints(&self) -> impl Iterator<Item = (usize, Watchpoint)> + '_ {
+        self.watchpoints
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, watch)| watch.map(|watch| (idx, watch)))
+    }
+
+    fn ended(&self) -> bool {
+        self.exe.pc() >= self.exe.program().len()
+    }
+
+    /// Execute one instruction and check whether anything should stop us.
+    fn step_checked(&mut self) -> Option<Stop> {
+        if self.ended() {
+            return Some(Stop::End);
+        }
+        let before = *self.exe.registers();
+        let pc = self.exe.pc();
+        if !self.started {
+            self.started = true;
+            if self.breakpoints.contains(&pc) {
+                return Some(Stop::Breakpoint(pc));
+            }
+        }
+        if let Err(e) = self.exe.step() {
+            return Some(Stop::Error(e));
+        }
+        *self.visits.entry(pc).or_insert(0) += 1;
+        let after = *self.exe.registers();
+
+        for (index, watch) in self.watchpoints() {
+            let (old, new) = (before[watch.register], after[watch.register]);
+            if watch.condition.fires(old, new) {
+                return Some(Stop::Watchpoint { index, old, new });
+            }
+        }
+
+        let pc = self.exe.pc();
+        if self.ended() {
+            Some(Stop::End)
+        } else if self.breakpoints.contains(&pc) {
+            Some(Stop::Breakpoint(pc))
+        } else if self.revisited_at.is_none() && self.visits.contains_key(&pc) {
+            self.revisited_at = Some(self.executed());
+            Some(Stop::Revisit(pc))
+        } else {
+            None
+        }
+    }
+
+    /// Execute up to `count` instructions, stopping early if anything else stops us.
+    pub fn step(&mut self, count: usize) -> Stop {
+        for _ in 0..count {
+            if let Some(stop) = self.step_checked() {
+                return stop;
+            }
+        }
+        Stop::Stepped
+    }
+
+    /// How many instructions have been executed
+    fn executed(&self) -> usize {
+        self.exe.history().map_or(0, |history| history.len())
+    }
+
+    /// Undo up to `count` instructions.
+    /// Going back past where it stopped for a revisit means it'll stop there again.
+    /// Returns how many were undone, which is less if it got back to the start.
+    pub fn back(&mut self, count: usize) -> usize {
+        for undone in 0..count {
+            if self.exe.step_back().is_none() {
+                return undone;
+            }
+            let pc = self.exe.pc();
+            if let Some(visits) = self.visits.get_mut(&pc) {
+                *visits -= 1;
+                if *visits == 0 {
+                    self.visits.remove(&pc);
+                }
+            }
+            if self.revisited_at.is_some_and(|at| self.executed() < at) {
+                self.revisited_at = None;
+            }
+        }
+        count
+    }
+
+    /// Run until something stops us.
+    ///
+    /// This always executes at least one instruction (unless the program has ended,
+    /// or there's a breakpoint on the very first one), so calling it again after a breakpoint
+    /// carries on past it.
+    /// Revisits only stop it once, so if the program loops forever and nothing else stops it,
+    /// it gives up with [`Stop::Limit`] after the run limit instead of growing the trace forever.
+    pub fn run(&mut self) -> Stop {
+        match self.step(self.run_limit) {
+            Stop::Stepped => Stop::Limit(self.run_limit),
+            stop => stop,
+        }
+    }
+
+    /// Read commands from `input` and write what happens to `output` until it's told to quit
+    /// or the input runs out. The commands are listed in [`HELP`].
+    pub fn interactive(&mut self, input: impl BufRead, mut output: impl Write) -> Result<()> {
+        self.print_position(&mut output)?;
+        write!(output, "> ")?;
+        output.flush()?;
+
+        for line in input.lines() {
+            let line = line?;
+            let line = line.trim();
+            if line == "q" || line == "quit" {
+                break;
+            }
+            if !line.is_empty() {
+                if let Err(e) = self.command(line, &mut output) {
+                    writeln!(output, "error: {:#}", e)?;
+                }
+            }
+            write!(output, "> ")?;
+            output.flush()?;
+        }
+        writeln!(output)?;
+        Ok(())
+    }
+
+    /// Run one interactive command
+    fn command(&mut self, line: &str, output: &mut impl Write) -> Result<()> {
+        let (cmd, rest) = match line.split_once(char::is_whitespace) {
+            Some((cmd, rest)) => (cmd, rest.trim()),
+            None => (line, ""),
+        };
+        let number = |default: Option<usize>| -> Result<usize> {
+            if rest.is_empty() {
+                default.ok_or_else(|| anyhow!("`{}` needs a number", cmd))
+            } else {
+                rest.parse()
+                    .with_context(|| format!("bad number `{}`", rest))
+            }
+        };
+
+        match cmd {
+            "s" | "step" => {
+                let count = number(Some(1))?;
+                let trace_len = self.exe.trace().map_or(0, |trace| trace.len());
+                let stop = self.step(count);
+                self.print_trace_since(trace_len, output)?;
+                self.print_stop(&stop, output)?;
+            }
+            "c" | "continue" => {
+                let stop = self.run();
+                self.print_stop(&stop, output)?;
+            }
+            "b" | "break" => {
+                let pc = number(None)?;
+                if self.remove_breakpoint(pc) {
+                    writeln!(output, "removed breakpoint at {}", pc)?;
+                } else {
+                    self.add_breakpoint(pc);
+                    writeln!(output, "added breakpoint at {}", pc)?;
+                }
+            }
+            "w" | "watch" => {
+                let watch: Watchpoint = rest.parse()?;
+                let index = self.add_watchpoint(watch);
+                writeln!(output, "watchpoint {}: {}", index, watch)?;
+            }
+            "d" | "delete" => {
+                let index = number(None)?;
+                match self.remove_watchpoint(index) {
+                    Some(watch) => writeln!(output, "deleted watchpoint {}: {}", index, watch)?,
+                    None => bail!("there's no watchpoint {}", index),
+                }
+            }
+            "h" | "help" => writeln!(output, "{}", HELP)?,
+            "r" | "regs" => self.print_registers(output)?,
+            "l" | "list" => self.print_listing(output)?,
+            "u" | "back" => {
+                let count = number(Some(1))?;
+                let undone = self.back(count);
+                if undone < count {
+                    writeln!(output, "back at the start after undoing {}", undone)?;
+                }
+                self.print_position(output)?;
+            }
+            "t" | "trace" => {
+                let count = number(Some(10))?;
+                let len = self.exe.trace().map_or(0, |trace| trace.len());
+                self.print_trace_since(len.saturating_sub(count), output)?;
+            }
+            oh_no => bail!("unknown command `{}`", oh_no),
+        }
+        Ok(())
+    }
+
+    fn print_trace_since(&self, start: usize, output: &mut impl Write) -> Result<()> {
+        if let Some(trace) = self.exe.trace() {
+            for entry in &trace[start..] {
+                writeln!(output, "{}", entry)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn print_stop(&self, stop: &Stop, output: &mut impl Write) -> Result<()> {
+        if *stop != Stop::Stepped {
+            writeln!(output, "{}", stop)?;
+        }
+        self.print_position(output)
+    }
+
+    fn print_position(&self, output: &mut impl Write) -> Result<()> {
+        let pc = self.exe.pc();
+        match self.exe.instruction(pc) {
+            Some(instr) => writeln!(output, "pc {}: {}", pc, instr)?,
+            None => writeln!(output, "pc {}: (end)", pc)?,
+        }
+        Ok(())
+    }
+
+    fn print_registers(&self, output: &mut impl Write) -> Result<()> {
+        let regs = self.exe.registers();
+        for &reg in Register::ALL.iter() {
+            write!(output, "{}={} ", reg, regs[reg])?;
+        }
+        writeln!(output)?;
+        Ok(())
+    }
+
+    fn print_listing(&self, output: &mut impl Write) -> Result<()> {
+        let pc = self.exe.pc();
+        let start = pc.saturating_sub(5);
+        let end = (pc + 6).min(self.exe.program().len());
+        for idx in start..end {
+            // unwrap is ok because idx is in the program
+            let instr = self.exe.instruction(idx).unwrap();
+            let marker = if idx == pc { "->" } else { "  " };
+            let bp = if self.breakpoints.contains(&idx) {
+                "*"
+            } else {
+                " "
+            };
+            writeln!(output, "{}{}{:>4}: {}", marker, bp, idx, instr)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_debugger() -> Result<()> {
+    // the program from day 8
+    let exe = Executor::new(
+        r"nop +0
+acc +1
+jmp +4
+acc +3
+jmp -3
+acc -99
+acc +1
+jmp -4
+acc +6",
+    )?;
+    let mut dbg = Debugger::new(exe);
+    dbg.add_breakpoint(6);
+    let watch = dbg.add_watchpoint("acc > 3".parse()?);
+
+    assert_eq!(dbg.step(2), Stop::Stepped);
+    assert_eq!(dbg.executor().get_accumulator(), 1);
+    assert_eq!(dbg.run(), Stop::Breakpoint(6));
+    assert_eq!(
+        dbg.run(),
+        Stop::Watchpoint {
+            index: watch,
+            old: 2,
+            new: 5
+        }
+    );
+    assert_eq!(dbg.run(), Stop::Revisit(1));
+    assert_eq!(dbg.executor().get_accumulator(), 5);
+
+    let trace = dbg.executor().trace().unwrap();
+    assert_eq!(trace.len(), 7);
+    assert_eq!(trace[3].pc, 6);
+    assert_eq!(trace[3].next_pc, 7);
+    assert_eq!(trace[3].before[Register::Acc], 1);
+    assert_eq!(trace[3].after[Register::Acc], 2);
+
+    // going back past the loop means it gets noticed again
+    assert_eq!(dbg.back(4), 4);
+    assert_eq!(dbg.executor().pc(), 6);
+    assert_eq!(dbg.executor().get_accumulator(), 1);
+    assert_eq!(dbg.executor().trace().unwrap().len(), 3);
+    dbg.remove_breakpoint(6);
+    dbg.remove_watchpoint(watch);
+    assert_eq!(dbg.run(), Stop::Revisit(1));
+    assert_eq!(dbg.back(100), 7);
+    assert_eq!(dbg.executor().pc(), 0);
+
+    // a breakpoint on the first instruction stops it before it starts
+    let mut dbg = Debugger::new(Executor::new("nop +0\njmp -1")?);
+    dbg.add_breakpoint(0);
+    assert_eq!(dbg.run(), Stop::Breakpoint(0));
+    assert_eq!(dbg.executor().trace().unwrap().len(), 0);
+    dbg.remove_breakpoint(0);
+    assert_eq!(dbg.run(), Stop::Revisit(0));
+    // after that nothing would ever stop it
+    dbg.set_run_limit(50);
+    assert_eq!(dbg.run(), Stop::Limit(50));
+    assert_eq!(dbg.executor().trace().unwrap().len(), 2 + 50);
+
+    Ok(())
+}
+
+#[test]
+fn test_interactive() -> Result<()> {
+    let exe = Executor::new("acc +1\njmp +2\nacc +10\nacc -3")?;
+    let mut dbg = Debugger::new(exe);
+    let mut output = Vec::new();
+    dbg.interactive(
+        "s\nb 3\nw acc\nc\nc\nt\nr\nfoo\nc\nu 2\nq\ns".as_bytes(),
+        &mut output,
+    )?;
+    let output = String::from_utf8(output)?;
+    assert!(output.contains("   1: jmp +2"));
+    assert!(output.contains("hit breakpoint at 3"));
+    assert!(output.contains("watchpoint 0 fired: 1 -> -2"));
+    assert!(output.contains("acc=-2 a=0"));
+    assert!(output.contains("unknown command `foo`"));
+    assert!(output.contains("program ended"));
+    assert!(output.contains("pc 1: jmp +2"));
+    assert_eq!(dbg.executor().get_accumulator(), 1);
+
+    Ok(())
+}
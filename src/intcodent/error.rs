@@ -0,0 +1,106 @@
+use std::{
+    error::Error,
+    fmt::{Display, Formatter},
+};
+
+use super::Register;
+
+/// Why an instruction couldn't be executed.
+///
+/// The executor is left exactly as it was before the instruction that failed.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ExecError {
+    /// The pc is past the end of the program, so there's nothing to execute
+    Halted { pc: usize },
+    /// A jump would go before the start of the program,
+    /// or further past the end than the instruction right after the last one
+    JumpOutOfBounds { pc: usize, offset: i64 },
+    /// Changing the accumulator would overflow
+    AccumulatorOverflow {
+        pc: usize,
+        value: i64,
+        argument: i64,
+    },
+    /// Changing some other register would overflow
+    RegisterOverflow {
+        pc: usize,
+        register: Register,
+        value: i64,
+        argument: i64,
+    },
+    /// `div` or `mod` by zero
+    DivideByZero { pc: usize },
+    /// A custom operation that isn't in the executor's instruction set
+    UnknownOperation { pc: usize, mnemonic: &'static str },
+}
+
+impl ExecError {
+    /// Get the pc of the instruction that failed.
+    pub fn pc(&self) -> usize {
+        match *self {
+            ExecError::Halted { pc }
+            | ExecError::JumpOutOfBounds { pc, .. }
+            | ExecError::AccumulatorOverflow { pc, .. }
+            | ExecError::RegisterOverflow { pc, .. }
+            | ExecError::DivideByZero { pc }
+            | ExecError::UnknownOperation { pc, .. } => pc,
+        }
+    }
+
+    /// Make the right overflow error for the register
+    pub(super) fn overflow(pc: usize, register: Register, value: i64, argument: i64) -> Self {
+        match register {
+            Register::Acc => ExecError::AccumulatorOverflow {
+                pc,
+                value,
+                argument,
+            },
+            _ => ExecError::RegisterOverflow {
+                pc,
+                register,
+                value,
+                argument,
+            },
+        }
+    }
+}
+
+impl Display for ExecError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExecError::Halted { pc } => write!(f, "the program has already halted (pc is {})", pc),
+            ExecError::JumpOutOfBounds { pc, offset } => write!(
+                f,
+                "at {}: jumping by {:+} goes out of the program",
+                pc, offset
+            ),
+            ExecError::AccumulatorOverflow {
+                pc,
+                value,
+                argument,
+            } => write!(
+                f,
+                "at {}: the accumulator overflowed (it was {}, the argument was {:+})",
+                pc, value, argument
+            ),
+            ExecError::RegisterOverflow {
+                pc,
+                register,
+                value,
+                argument,
+            } => write!(
+                f,
+                "at {}: register {} overflowed (it was {}, the argument was {:+})",
+                pc, register, value, argument
+            ),
+            ExecError::DivideByZero { pc } => write!(f, "at {}: divided by zero", pc),
+            ExecError::UnknownOperation { pc, mnemonic } => write!(
+                f,
+                "at {}: `{}` isn't in this executor's instruction set",
+                pc, mnemonic
+            ),
+        }
+    }
+}
+
+impl Error for ExecError {}
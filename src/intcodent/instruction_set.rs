@@ -0,0 +1,240 @@
+use anyhow::{anyhow, bail, Context, Result};
+use once_cell::sync::Lazy;
+
+use std::{collections::HashMap, sync::Arc};
+
+use super::{
+    assembler::assemble,
+    instructions::{CustomOp, Instruction, Operation, Register, Registers},
+};
+
+/// What a custom opcode does when it's executed.
+///
+/// It gets all the registers, the registers the instruction was written with,
+/// and the instruction's argument (0 if it doesn't take one).
+///
+/// Handlers should do their arithmetic checked, like the built-in opcodes do,
+/// and return [`Flow::Overflow`] instead of wrapping.
+/// The registers are a copy, so anything the handler changed before that gets thrown away.
+pub type Handler = fn(&mut Registers, &[Register], i64) -> Flow;
+
+/// Turns the registers a built-in opcode was written with into its operation.
+type Builder = fn(&[Register]) -> Operation;
+
+/// Where execution goes after a custom opcode.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Flow {
+    /// Go on to the next instruction
+    Next,
+    /// Jump by this offset
+    Jump(i64),
+    /// Stop the program
+    Halt,
+    /// Changing this register would overflow, so the instruction fails
+    /// with an overflow error and nothing changes
+    Overflow(Register),
+}
+
+/// The standard instruction set, with every built-in opcode.
+pub static STANDARD: Lazy<Arc<InstructionSet>> = Lazy::new(|| Arc::new(InstructionSet::new()));
+
+/// A table of opcodes that can be parsed and executed.
+///
+/// Starts with all the built-in opcodes, and more can be added with [`InstructionSet::add_opcode`].
+#[derive(Clone)]
+pub struct InstructionSet {
+    opcodes: Vec<Opcode>,
+    /// Maps mnemonics to indices in `opcodes`
+    by_mnemonic: HashMap<&'static str, usize>,
+}
+
+#[derive(Clone)]
+struct Opcode {
+    mnemonic: &'static str,
+    /// How many registers it's written with
+    registers: usize,
+    takes_argument: bool,
+    kind: OpcodeKind,
+}
+
+#[derive(Clone)]
+enum OpcodeKind {
+    Builtin(Builder),
+    Custom(Handler),
+}
+
+impl InstructionSet {
+    /// Make an instruction set with all the built-in opcodes.
+    pub fn new() -> Self {
+        let builtins: [(&'static str, usize, bool, Builder); 13] = [
+            ("acc", 0, true, |_| Operation::Acc),
+            ("jmp", 0, true, |_| Operation::Jmp),
+            ("nop", 0, true, |_| Operation::Nop),
+            ("set", 1, true, |regs| Operation::Set(regs[0])),
+            ("add", 1, true, |regs| Operation::Add(regs[0])),
+            ("mul", 1, true, |regs| Operation::Mul(regs[0])),
+            ("div", 1, true, |regs| Operation::Div(regs[0])),
+            ("mod", 1, true, |regs| Operation::Mod(regs[0])),
+            ("cpy", 2, false, |regs| Operation::Cpy(regs[0], regs[1])),
+            ("jz", 1, true, |regs| Operation::Jz(regs[0])),
+            ("jnz", 1, true, |regs| Operation::Jnz(regs[0])),
+            ("jgz", 1, true, |regs| Operation::Jgz(regs[0])),
+            ("jlz", 1, true, |regs| Operation::Jlz(regs[0])),
+        ];
+
+        let mut out = Self {
+            opcodes: Vec::new(),
+            by_mnemonic: HashMap::new(),
+        };
+        for &(mnemonic, registers, takes_argument, build) in builtins.iter() {
+            out.insert(Opcode {
+                mnemonic,
+                registers,
+                takes_argument,
+                kind: OpcodeKind::Builtin(build),
+            });
+        }
+        out
+    }
+
+    fn insert(&mut self, opcode: Opcode) {
+        self.by_mnemonic.insert(opcode.mnemonic, self.opcodes.len());
+        self.opcodes.push(opcode);
+    }
+
+    /// Add a new opcode.
+    ///
+    /// It's written as the mnemonic, then `registers` register names (at most 2),
+    /// then a signed argument if `takes_argument` is set.
+    pub fn add_opcode(
+        &mut self,
+        mnemonic: &'static str,
+        registers: usize,
+        takes_argument: bool,
+        handler: Handler,
+    ) -> Result<()> {
+        if self.by_mnemonic.contains_key(mnemonic) {
+            bail!("there's already an opcode called `{}`", mnemonic);
+        }
+        if registers > 2 {
+            bail!("opcodes can have at most 2 registers, not {}", registers);
+        }
+        if mnemonic.is_empty() || mnemonic.contains(char::is_whitespace) {
+            bail!("`{}` can't be a mnemonic", mnemonic);
+        }
+        self.insert(Opcode {
+            mnemonic,
+            registers,
+            takes_argument,
+            kind: OpcodeKind::Custom(handler),
+        });
+        Ok(())
+    }
+
+    /// Get the handler for a custom operation parsed by this set.
+    pub fn handler(&self, op: CustomOp) -> Option<Handler> {
+        match self.opcodes.get(op.id) {
+            Some(Opcode {
+                mnemonic,
+                kind: OpcodeKind::Custom(handler),
+                ..
+            }) if *mnemonic == op.mnemonic => Some(*handler),
+            _ => None,
+        }
+    }
+
+    /// Iterate over the mnemonics of every opcode in here.
+    pub fn mnemonics(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.opcodes.iter().map(|opcode| opcode.mnemonic)
+    }
+
+    /// Get how an opcode is written: how many registers it takes, and whether it takes an argument.
+    pub fn signature(&self, mnemonic: &str) -> Option<(usize, bool)> {
+        let opcode = &self.opcodes[*self.by_mnemonic.get(mnemonic)?];
+        Some((opcode.registers, opcode.takes_argument))
+    }
+
+    /// Make an instruction out of its parts.
+    ///
+    /// The argument is ignored if the opcode doesn't take one.
+    pub fn build(&self, mnemonic: &str, regs: &[Register], argument: i64) -> Result<Instruction> {
+        let id = *self
+            .by_mnemonic
+            .get(mnemonic)
+            .ok_or_else(|| anyhow!("unknown opcode `{}`", mnemonic))?;
+        let opcode = &self.opcodes[id];
+        if regs.len() != opcode.registers {
+            bail!(
+                "`{}` expects {} register(s), found {}",
+                mnemonic,
+                opcode.registers,
+                regs.len()
+            );
+        }
+
+        let operation = match opcode.kind {
+            OpcodeKind::Builtin(build) => build(regs),
+            OpcodeKind::Custom(_) => {
+                let mut registers = [None; 2];
+                for (slot, &reg) in registers.iter_mut().zip(regs.iter()) {
+                    *slot = Some(reg);
+                }
+                Operation::Custom(CustomOp {
+                    id,
+                    mnemonic: opcode.mnemonic,
+                    registers,
+                    takes_argument: opcode.takes_argument,
+                })
+            }
+        };
+        Ok(Instruction {
+            operation,
+            argument: if opcode.takes_argument { argument } else { 0 },
+        })
+    }
+
+    /// Parse one instruction, like `jmp -3` or `add b +7`.
+    pub fn parse_instruction(&self, s: &str) -> Result<Instruction> {
+        let split = s.split_ascii_whitespace().collect::<Vec<_>>();
+        let (op, operands) = split
+            .split_first()
+            .ok_or_else(|| anyhow!("expected an instruction, found nothing"))?;
+        let (registers, takes_argument) = self
+            .signature(op)
+            .ok_or_else(|| anyhow!("unknown opcode `{}`", op))?;
+
+        let expected = registers + takes_argument as usize;
+        if operands.len() != expected {
+            bail!(
+                "`{}` expects {} operand(s), found {}",
+                op,
+                expected,
+                operands.len()
+            );
+        }
+        let (regs, arg) = operands.split_at(registers);
+        let regs = regs
+            .iter()
+            .map(|reg| reg.parse())
+            .collect::<Result<Vec<Register>>>()?;
+        let argument = match arg.first() {
+            Some(arg) => arg
+                .parse()
+                .with_context(|| format!("bad argument `{}`", arg))?,
+            None => 0,
+        };
+        self.build(op, &regs, argument)
+    }
+
+    /// Parse an input into a program with [`assemble`](super::assembler::assemble),
+    /// so it can have labels and comments.
+    pub fn parse_program(&self, i: &str) -> Result<Vec<Instruction>> {
+        Ok(assemble(i, self)?)
+    }
+}
+
+impl Default for InstructionSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
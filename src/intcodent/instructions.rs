@@ -0,0 +1,223 @@
+use anyhow::{bail, Error, Result};
+
+use std::{
+    fmt::{Display, Formatter},
+    ops::{Index, IndexMut},
+    str::FromStr,
+};
+
+/// How many registers there are
+pub const REGISTER_COUNT: usize = 5;
+
+/// A register in the executor.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub enum Register {
+    /// The accumulator, which the original opcodes work on
+    Acc,
+    A,
+    B,
+    C,
+    D,
+}
+
+impl Register {
+    /// Every register, in order
+    pub const ALL: [Register; REGISTER_COUNT] = [
+        Register::Acc,
+        Register::A,
+        Register::B,
+        Register::C,
+        Register::D,
+    ];
+
+    /// Get the name of this register as it's written in a program
+    pub fn name(self) -> &'static str {
+        match self {
+            Register::Acc => "acc",
+            Register::A => "a",
+            Register::B => "b",
+            Register::C => "c",
+            Register::D => "d",
+        }
+    }
+}
+
+impl Display for Register {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Register {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match Register::ALL.iter().find(|reg| reg.name() == s) {
+            Some(&reg) => Ok(reg),
+            None => bail!("unknown register `{}`", s),
+        }
+    }
+}
+
+/// The contents of all the registers.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub struct Registers([i64; REGISTER_COUNT]);
+
+impl Index<Register> for Registers {
+    type Output = i64;
+    fn index(&self, reg: Register) -> &i64 {
+        &self.0[reg as usize]
+    }
+}
+
+impl IndexMut<Register> for Registers {
+    fn index_mut(&mut self, reg: Register) -> &mut i64 {
+        &mut self.0[reg as usize]
+    }
+}
+
+/// Whether a conditional jump jumps for a given register value
+pub type Condition = fn(i64) -> bool;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Instruction {
+    pub operation: Operation,
+    pub argument: i64,
+}
+
+impl Display for Instruction {
+    /// Write this the way it'd be written in a program, like `jmp -3` or `add b +7`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.operation.mnemonic())?;
+        let registers = self.operation.registers();
+        for reg in registers.iter().flatten() {
+            write!(f, " {}", reg)?;
+        }
+        if self.operation.takes_argument() {
+            write!(f, " {:+}", self.argument)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Operation {
+    /// Add/sub to the accumulator
+    Acc,
+    /// Jump by this offset
+    Jmp,
+    /// Do nothing
+    Nop,
+
+    /// Set the register to the argument
+    Set(Register),
+    /// Add the argument to the register
+    Add(Register),
+    /// Multiply the register by the argument
+    Mul(Register),
+    /// Divide the register by the argument, rounding towards zero
+    Div(Register),
+    /// Set the register to the (always positive) remainder of it divided by the argument
+    Mod(Register),
+    /// Copy the second register into the first
+    Cpy(Register, Register),
+
+    /// Jump by the argument if the register is zero
+    Jz(Register),
+    /// Jump by the argument if the register is not zero
+    Jnz(Register),
+    /// Jump by the argument if the register is greater than zero
+    Jgz(Register),
+    /// Jump by the argument if the register is less than zero
+    Jlz(Register),
+
+    /// An opcode that was added to an [`InstructionSet`](super::InstructionSet) at runtime
+    Custom(CustomOp),
+}
+
+impl Operation {
+    /// Get the name of this operation as it's written in a program
+    pub fn mnemonic(self) -> &'static str {
+        match self {
+            Operation::Acc => "acc",
+            Operation::Jmp => "jmp",
+            Operation::Nop => "nop",
+            Operation::Set(_) => "set",
+            Operation::Add(_) => "add",
+            Operation::Mul(_) => "mul",
+            Operation::Div(_) => "div",
+            Operation::Mod(_) => "mod",
+            Operation::Cpy(..) => "cpy",
+            Operation::Jz(_) => "jz",
+            Operation::Jnz(_) => "jnz",
+            Operation::Jgz(_) => "jgz",
+            Operation::Jlz(_) => "jlz",
+            Operation::Custom(op) => op.mnemonic,
+        }
+    }
+
+    /// Get the registers this is written with, in order
+    pub fn registers(self) -> [Option<Register>; 2] {
+        match self {
+            Operation::Acc | Operation::Jmp | Operation::Nop => [None, None],
+            Operation::Set(reg)
+            | Operation::Add(reg)
+            | Operation::Mul(reg)
+            | Operation::Div(reg)
+            | Operation::Mod(reg)
+            | Operation::Jz(reg)
+            | Operation::Jnz(reg)
+            | Operation::Jgz(reg)
+            | Operation::Jlz(reg) => [Some(reg), None],
+            Operation::Cpy(dst, src) => [Some(dst), Some(src)],
+            Operation::Custom(op) => op.registers,
+        }
+    }
+
+    /// Is this written with a numeric argument?
+    pub fn takes_argument(self) -> bool {
+        match self {
+            Operation::Cpy(..) => false,
+            Operation::Custom(op) => op.takes_argument,
+            _ => true,
+        }
+    }
+
+    /// Might this jump somewhere other than the next instruction?
+    ///
+    /// Custom operations might, so they count.
+    pub fn is_jump(self) -> bool {
+        matches!(
+            self,
+            Operation::Jmp
+                | Operation::Jz(_)
+                | Operation::Jnz(_)
+                | Operation::Jgz(_)
+                | Operation::Jlz(_)
+                | Operation::Custom(_)
+        )
+    }
+
+    /// If this is a conditional jump, get the register it checks
+    /// and whether it jumps for a given value.
+    pub fn condition(self) -> Option<(Register, Condition)> {
+        match self {
+            Operation::Jz(reg) => Some((reg, |val| val == 0)),
+            Operation::Jnz(reg) => Some((reg, |val| val != 0)),
+            Operation::Jgz(reg) => Some((reg, |val| val > 0)),
+            Operation::Jlz(reg) => Some((reg, |val| val < 0)),
+            _ => None,
+        }
+    }
+}
+
+/// An operation defined at runtime.
+///
+/// Which handler runs it is looked up in the executor's instruction set by `id`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct CustomOp {
+    /// Index of this opcode in its instruction set
+    pub id: usize,
+    pub mnemonic: &'static str,
+    pub registers: [Option<Register>; 2],
+    pub takes_argument: bool,
+}
@@ -1,15 +1,57 @@
-use std::{collections::HashSet, str::FromStr};
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+    sync::Arc,
+};
 
-use anyhow::{bail, Context, Error, Result};
+use anyhow::{bail, Error, Result};
 use std::result::Result as StdResult;
 
+pub mod abstract_interp;
+pub mod analysis;
+mod assembler;
+mod debugger;
+mod error;
+mod instruction_set;
+mod instructions;
+
+pub use assembler::{assemble, disassemble, AsmError};
+pub use debugger::{
+    Debugger, Stop, TraceEntry, WatchCondition, Watchpoint, DEFAULT_RUN_LIMIT,
+    HELP as DEBUGGER_HELP,
+};
+pub use error::ExecError;
+pub use instruction_set::{Flow, Handler, InstructionSet, STANDARD};
+pub use instructions::{
+    Condition, CustomOp, Instruction, Operation, Register, Registers, REGISTER_COUNT,
+};
+
+/// The program is shared between clones, so an executor can be forked partway through
+/// to try something out. Cloning does copy any patches, trace and history though,
+/// so turn those off first (or use [`Executor::snapshot`]) if it's going to be done a lot.
+#[derive(Clone)]
 pub struct Executor {
-    acc: i64,
+    registers: Registers,
 
     /// Program counter: the index of the instruction we're about to execute
     pc: usize,
     /// The instructions
-    program: Vec<Instruction>,
+    program: Arc<[Instruction]>,
+    /// Instructions that have been swapped out for this executor only
+    patches: HashMap<usize, Instruction>,
+    /// Where to find handlers for custom operations
+    instruction_set: Arc<InstructionSet>,
+    /// Every instruction executed so far, if tracing is on
+    trace: Option<Vec<TraceEntry>>,
+    /// The state before every instruction executed so far, if history is on
+    history: Option<Vec<Snapshot>>,
+}
+
+/// The state of an executor at one point, which it can be put back to.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Snapshot {
+    pub pc: usize,
+    pub registers: Registers,
 }
 
 impl Executor {
@@ -19,41 +61,251 @@ impl Executor {
         Ok(Executor::new_from_program(program))
     }
 
+    /// Create a new executor from the input, which can use the opcodes in the given instruction set.
+    pub fn with_instruction_set(i: &str, instruction_set: Arc<InstructionSet>) -> Result<Self> {
+        let program = instruction_set.parse_program(i)?;
+        Ok(Self {
+            instruction_set,
+            ..Executor::new_from_program(program)
+        })
+    }
+
     /// Create a new executor from a pre-parsed input
     pub fn new_from_program(program: Vec<Instruction>) -> Self {
         Self {
-            acc: 0,
+            registers: Registers::default(),
             pc: 0,
-            program,
+            program: program.into(),
+            patches: HashMap::new(),
+            instruction_set: STANDARD.clone(),
+            trace: None,
+            history: None,
         }
     }
 
-    /// Parse an input into a program.
+    /// Parse an input into a program using the standard instruction set.
     pub fn parse_program(i: &str) -> Result<Vec<Instruction>> {
-        i.lines()
-            .enumerate()
-            .map(|(idx, line)| line.parse().context(format!("on line {}", idx + 1)))
-            .collect::<Result<_>>()
+        STANDARD.parse_program(i)
     }
 
     /// Get the value of the accumulator.
     pub fn get_accumulator(&self) -> i64 {
-        self.acc
+        self.registers[Register::Acc]
+    }
+
+    /// Get the value of any register.
+    pub fn get_register(&self, reg: Register) -> i64 {
+        self.registers[reg]
+    }
+
+    /// Get the values of all the registers.
+    pub fn registers(&self) -> &Registers {
+        &self.registers
+    }
+
+    /// Get the index of the instruction we're about to execute.
+    pub fn pc(&self) -> usize {
+        self.pc
+    }
+
+    /// Get the program, without any patches.
+    pub fn program(&self) -> &[Instruction] {
+        &self.program
+    }
+
+    /// Get the instruction at `pc`, with any patch applied.
+    pub fn instruction(&self, pc: usize) -> Option<&Instruction> {
+        self.patches.get(&pc).or_else(|| self.program.get(pc))
+    }
+
+    /// Swap out the instruction at `pc` for this executor only,
+    /// without touching the program it shares with its clones.
+    /// Returns what was there before.
+    ///
+    /// Panics if `pc` is outside the program.
+    pub fn patch(&mut self, pc: usize, instruction: Instruction) -> Instruction {
+        assert!(
+            pc < self.program.len(),
+            "can't patch {} in a program of length {}",
+            pc,
+            self.program.len()
+        );
+        let old = self.instruction(pc).unwrap().clone();
+        if self.program[pc] == instruction {
+            self.patches.remove(&pc);
+        } else {
+            self.patches.insert(pc, instruction);
+        }
+        old
+    }
+
+    /// Get the current state.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            pc: self.pc,
+            registers: self.registers,
+        }
+    }
+
+    /// Go back (or forward) to a state.
+    ///
+    /// This doesn't touch the trace or the history.
+    pub fn restore(&mut self, snapshot: Snapshot) {
+        self.pc = snapshot.pc;
+        self.registers = snapshot.registers;
+    }
+
+    /// Start recording the state before every instruction, so [`Executor::step_back`] can undo them.
+    /// Does nothing if it's already recording.
+    pub fn enable_history(&mut self) {
+        if self.history.is_none() {
+            self.history = Some(Vec::new());
+        }
+    }
+
+    /// Stop recording and throw away the history.
+    pub fn disable_history(&mut self) {
+        self.history = None;
+    }
+
+    /// Get the state before every instruction executed since history was turned on,
+    /// or None if it's off.
+    pub fn history(&self) -> Option<&[Snapshot]> {
+        self.history.as_deref()
+    }
+
+    /// Undo the last instruction executed, going back to the state before it.
+    /// Its trace entry is removed too, if tracing is on.
+    ///
+    /// Returns the state that was undone,
+    /// or None if history is off or there's nothing to undo.
+    pub fn step_back(&mut self) -> Option<Snapshot> {
+        let previous = self.history.as_mut()?.pop()?;
+        let undone = self.snapshot();
+        self.restore(previous);
+        if let Some(trace) = &mut self.trace {
+            trace.pop();
+        }
+        Some(undone)
+    }
+
+    /// Start recording every instruction that's executed.
+    /// Does nothing if it's already recording.
+    pub fn enable_trace(&mut self) {
+        if self.trace.is_none() {
+            self.trace = Some(Vec::new());
+        }
+    }
+
+    /// Stop recording and throw away what's been recorded.
+    pub fn disable_trace(&mut self) {
+        self.trace = None;
+    }
+
+    /// Get everything recorded so far, or None if tracing is off.
+    pub fn trace(&self) -> Option<&[TraceEntry]> {
+        self.trace.as_deref()
+    }
+
+    /// Take everything recorded so far, leaving tracing on with an empty trace.
+    pub fn take_trace(&mut self) -> Option<Vec<TraceEntry>> {
+        self.trace.as_mut().map(std::mem::take)
     }
 
     /// Execute one opcode.
-    pub fn step(&mut self) {
+    ///
+    /// If it can't be executed, the executor is left as it was and the error says why.
+    pub fn step(&mut self) -> StdResult<(), ExecError> {
+        let pc = self.pc;
+        let instruction = match self.instruction(pc) {
+            Some(it) => it.clone(),
+            None => return Err(ExecError::Halted { pc }),
+        };
         let Instruction {
             operation,
             argument,
-        } = &self.program[self.pc];
-        self.pc += 1;
-        match operation {
-            Operation::Acc => self.acc += argument,
-            // Subtract 1 because we already moved PC
-            Operation::Jmp => self.pc = ((self.pc as i64) + argument) as usize - 1,
-            Operation::Nop => {}
+        } = instruction.clone();
+
+        // Work on a copy so nothing changes if this fails
+        let before = self.registers;
+        let mut regs = self.registers;
+        // Store the result of some checked arithmetic on a register
+        let mut arith = |reg: Register, result: Option<i64>| match result {
+            Some(it) => {
+                regs[reg] = it;
+                Ok(Flow::Next)
+            }
+            None => Err(ExecError::overflow(pc, reg, before[reg], argument)),
+        };
+
+        let flow = match operation {
+            Operation::Acc => arith(Register::Acc, before[Register::Acc].checked_add(argument))?,
+            Operation::Jmp => Flow::Jump(argument),
+            Operation::Nop => Flow::Next,
+
+            Operation::Set(reg) => arith(reg, Some(argument))?,
+            Operation::Add(reg) => arith(reg, before[reg].checked_add(argument))?,
+            Operation::Mul(reg) => arith(reg, before[reg].checked_mul(argument))?,
+            Operation::Div(_) | Operation::Mod(_) if argument == 0 => {
+                return Err(ExecError::DivideByZero { pc })
+            }
+            Operation::Div(reg) => arith(reg, before[reg].checked_div(argument))?,
+            Operation::Mod(reg) => arith(reg, before[reg].checked_rem_euclid(argument))?,
+            Operation::Cpy(dst, src) => arith(dst, Some(before[src]))?,
+
+            Operation::Jz(_) | Operation::Jnz(_) | Operation::Jgz(_) | Operation::Jlz(_) => {
+                // unwrap is ok because these are all conditional jumps
+                let (reg, jumps) = operation.condition().unwrap();
+                if jumps(before[reg]) {
+                    Flow::Jump(argument)
+                } else {
+                    Flow::Next
+                }
+            }
+
+            Operation::Custom(op) => {
+                let handler =
+                    self.instruction_set
+                        .handler(op)
+                        .ok_or(ExecError::UnknownOperation {
+                            pc,
+                            mnemonic: op.mnemonic,
+                        })?;
+                let operands = op.registers.iter().flatten().copied().collect::<Vec<_>>();
+                handler(&mut regs, &operands, argument)
+            }
+        };
+
+        let next_pc = match flow {
+            Flow::Next => pc + 1,
+            // Jumps are relative to the jump instruction itself.
+            // Landing just past the end is how programs are meant to stop.
+            Flow::Jump(offset) => match (pc as i64).checked_add(offset) {
+                Some(target) if target >= 0 && target as usize <= self.program.len() => {
+                    target as usize
+                }
+                _ => return Err(ExecError::JumpOutOfBounds { pc, offset }),
+            },
+            Flow::Halt => self.program.len(),
+            Flow::Overflow(reg) => return Err(ExecError::overflow(pc, reg, before[reg], argument)),
+        };
+
+        let snapshot = self.snapshot();
+        if let Some(history) = &mut self.history {
+            history.push(snapshot);
+        }
+        self.registers = regs;
+        self.pc = next_pc;
+        if let Some(trace) = &mut self.trace {
+            trace.push(TraceEntry {
+                pc,
+                instruction,
+                before,
+                after: regs,
+                next_pc,
+            });
         }
+        Ok(())
     }
 
     /// Create an Executor from the input internally and run until just before one index would be executed twice.
@@ -64,75 +316,263 @@ impl Executor {
 
         while !indexes_execed.contains(&exe.pc) {
             indexes_execed.insert(exe.pc);
-            exe.step();
+            exe.step()?;
         }
 
-        Ok(exe.acc)
+        Ok(exe.get_accumulator())
     }
 
     /// Run until either the end,
     /// or just before one index would be executed twice.
-    /// Returns the value of the accumulator at that point.
-    /// Ok if it terminated normally, Err if it terminated because it was going to loop.
-    pub fn run_until_end_or_loop(&mut self) -> StdResult<i64, i64> {
+    /// Returns how it stopped and the value of the accumulator at that point,
+    /// or an error if an instruction couldn't be executed.
+    ///
+    /// With conditional jumps, running an index twice doesn't always mean it loops forever,
+    /// but this still stops there.
+    pub fn run_until_end_or_loop(&mut self) -> StdResult<Termination, ExecError> {
         let mut indexes_execed = HashSet::new();
 
         loop {
             if indexes_execed.contains(&self.pc) {
-                return Err(self.acc);
+                return Ok(Termination::Looped(self.get_accumulator()));
             }
             if self.pc >= self.program.len() {
-                return Ok(self.acc);
+                return Ok(Termination::Ended(self.get_accumulator()));
             }
 
             indexes_execed.insert(self.pc);
-            self.step();
+            self.step()?;
+        }
+    }
+
+    /// Run until the program counter goes past the end of the program.
+    /// Returns the value of the accumulator at that point.
+    ///
+    /// This never returns if the program loops forever.
+    pub fn run_until_end(&mut self) -> StdResult<i64, ExecError> {
+        while self.pc < self.program.len() {
+            self.step()?;
         }
+        Ok(self.get_accumulator())
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct Instruction {
-    pub operation: Operation,
-    pub argument: i64,
+/// How a run stopped without an error.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Termination {
+    /// The program counter went past the end, and this was in the accumulator
+    Ended(i64),
+    /// Some instruction was about to run a second time, and this was in the accumulator
+    Looped(i64),
 }
 
 impl FromStr for Instruction {
     type Err = Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let split = s.split_ascii_whitespace().collect::<Vec<_>>();
-        match split.as_slice() {
-            [op, arg] => {
-                let operation = op.parse()?;
-                let argument = arg.parse()?;
-                Ok(Instruction {
-                    operation,
-                    argument,
-                })
-            }
-            oh_no => bail!("expected 2 words, found {}", oh_no.len()),
-        }
+        STANDARD.parse_instruction(s)
     }
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
-pub enum Operation {
-    /// Add/sub to the accumulator
-    Acc,
-    /// Jump by this offset
-    Jmp,
-    /// Do nothing
-    Nop,
+#[test]
+fn test_parse() -> Result<()> {
+    let program = "nop +0\nacc -99\nset b +12\ncpy a b\njgz c -3";
+    let parsed = Executor::parse_program(program)?;
+    assert_eq!(parsed[2].operation, Operation::Set(Register::B));
+    assert_eq!(
+        parsed[3].operation,
+        Operation::Cpy(Register::A, Register::B)
+    );
+    let printed = parsed
+        .iter()
+        .map(|instr| instr.to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+    assert_eq!(printed, program);
+
+    assert!("nop".parse::<Instruction>().is_err());
+    assert!("cpy a +1".parse::<Instruction>().is_err());
+    assert!("set e +1".parse::<Instruction>().is_err());
+    assert!("foo +1".parse::<Instruction>().is_err());
+
+    Ok(())
 }
 
-impl FromStr for Operation {
-    type Err = Error;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(match s {
-            "acc" => Operation::Acc,
-            "jmp" => Operation::Jmp,
-            "nop" => Operation::Nop,
-            oh_no => bail!("unknown opcode `{}`", oh_no),
+#[test]
+fn test_registers() -> Result<()> {
+    // counts down from 10, adding 3 to acc each time
+    let mut exe = Executor::new(
+        r"set a +10
+acc +3
+add a -1
+jgz a -2
+cpy b acc
+mod b +7",
+    )?;
+    assert_eq!(exe.run_until_end()?, 30);
+    assert_eq!(exe.get_register(Register::B), 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_custom_opcode() -> Result<()> {
+    let mut set = InstructionSet::new();
+    // Square a register
+    set.add_opcode("sqr", 1, false, |regs, operands, _| {
+        let reg = operands[0];
+        match regs[reg].checked_mul(regs[reg]) {
+            Some(it) => {
+                regs[reg] = it;
+                Flow::Next
+            }
+            None => Flow::Overflow(reg),
+        }
+    })?;
+    // Stop if acc is over the argument
+    set.add_opcode("hgt", 0, true, |regs, _, limit| {
+        if regs[Register::Acc] > limit {
+            Flow::Halt
+        } else {
+            Flow::Next
+        }
+    })?;
+    assert!(set
+        .add_opcode("jmp", 0, true, |_, _, _| Flow::Next)
+        .is_err());
+
+    let set = Arc::new(set);
+    let mut exe = Executor::with_instruction_set(
+        r"acc +2
+sqr acc
+hgt +100
+jmp -2
+acc +1000",
+        set.clone(),
+    )?;
+    assert_eq!(exe.run_until_end()?, 256);
+
+    // squaring too much overflows like the built-in opcodes do
+    let mut exe = Executor::with_instruction_set("acc +4000000000\nsqr acc", set.clone())?;
+    exe.step()?;
+    assert_eq!(
+        exe.step(),
+        Err(ExecError::AccumulatorOverflow {
+            pc: 1,
+            value: 4000000000,
+            argument: 0
         })
+    );
+    assert_eq!(exe.get_register(Register::Acc), 4000000000);
+
+    // custom opcodes don't parse with the standard set
+    assert!(Executor::new("sqr acc").is_err());
+    assert_eq!(
+        set.parse_instruction("sqr b")?.to_string(),
+        "sqr b".to_string()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_errors() -> Result<()> {
+    let error = |program: &str| -> Result<ExecError> {
+        match Executor::new(program)?.run_until_end() {
+            Ok(acc) => bail!("expected an error, but it ended with {}", acc),
+            Err(e) => Ok(e),
+        }
+    };
+
+    assert_eq!(
+        error("jmp -1")?,
+        ExecError::JumpOutOfBounds { pc: 0, offset: -1 }
+    );
+    assert_eq!(
+        error("nop +0\njmp +2")?,
+        ExecError::JumpOutOfBounds { pc: 1, offset: 2 }
+    );
+    assert_eq!(
+        error("acc +9223372036854775807\nacc +1")?,
+        ExecError::AccumulatorOverflow {
+            pc: 1,
+            value: i64::MAX,
+            argument: 1
+        }
+    );
+    assert_eq!(
+        error("set a -9223372036854775808\ndiv a -1")?,
+        ExecError::RegisterOverflow {
+            pc: 1,
+            register: Register::A,
+            value: i64::MIN,
+            argument: -1
+        }
+    );
+    assert_eq!(error("mod b +0")?, ExecError::DivideByZero { pc: 0 });
+    // jumping to just past the end is fine
+    assert_eq!(Executor::new("acc +4\njmp +1")?.run_until_end()?, 4);
+
+    // nothing changes when a step fails
+    let mut exe = Executor::new("acc +5\njmp -3")?;
+    exe.step()?;
+    assert!(exe.step().is_err());
+    assert_eq!(exe.pc(), 1);
+    assert_eq!(exe.get_accumulator(), 5);
+
+    let mut exe = Executor::new("nop +0")?;
+    exe.step()?;
+    assert_eq!(exe.step(), Err(ExecError::Halted { pc: 1 }));
+
+    Ok(())
+}
+
+#[test]
+fn test_snapshots() -> Result<()> {
+    // the program from day 8
+    let mut exe = Executor::new(
+        r"nop +0
+acc +1
+jmp +4
+acc +3
+jmp -3
+acc -99
+acc +1
+jmp -4
+acc +6",
+    )?;
+    exe.enable_history();
+    for _ in 0..5 {
+        exe.step()?;
     }
+    assert_eq!(exe.pc(), 3);
+    assert_eq!(exe.get_accumulator(), 2);
+
+    // go back to just before the suspect jump at 7
+    let undone = exe.step_back().unwrap();
+    assert_eq!(undone.pc, 3);
+    assert_eq!(exe.pc(), 7);
+    assert_eq!(exe.history().unwrap().len(), 4);
+    let at_jump = exe.snapshot();
+
+    // try it with the jump and without
+    let mut patched = exe.clone();
+    patched.patch(7, "nop -4".parse()?);
+    assert_eq!(patched.run_until_end_or_loop()?, Termination::Ended(8));
+    // it only notices the loop once it gets back to 7
+    assert_eq!(exe.run_until_end_or_loop()?, Termination::Looped(7));
+    assert_eq!(exe.instruction(7), Some(&"jmp -4".parse()?));
+
+    exe.restore(at_jump);
+    assert_eq!(exe.pc(), 7);
+    assert_eq!(exe.get_accumulator(), 2);
+    while exe.step_back().is_some() {}
+    assert_eq!(
+        exe.snapshot(),
+        Snapshot {
+            pc: 0,
+            registers: Registers::default()
+        }
+    );
+
+    Ok(())
 }
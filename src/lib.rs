@@ -1,25 +1,35 @@
 #![allow(dead_code)]
 #![allow(unused_imports)]
-#![feature(iterator_fold_self)]
 #![feature(box_patterns)]
 
-mod day1;
-mod day2;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod day9;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day18;
+#[macro_use]
+pub mod input;
 
-mod intcodent;
+pub mod solution;
+#[macro_use]
+pub mod answers;
+pub mod bench;
+
+pub mod day1;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod day9;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day17;
+pub mod day18;
+
+pub mod grid;
+pub mod intcodent;
+pub mod records;
+pub mod schema;
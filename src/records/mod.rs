@@ -0,0 +1,224 @@
+//! Splitting up inputs made of chunks separated by blank lines,
+//! like the passports in day 4, the groups in day 6 and the sections in day 16.
+//!
+//! Lines can end in `\n` or `\r\n`, whitespace at the ends of lines is ignored,
+//! and a line with nothing but whitespace on it counts as blank.
+//! Line and column numbers start at 1, like an editor's.
+
+use std::fmt::{Display, Formatter};
+
+/// One line of a group, with where it was in the input.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Line<'a> {
+    /// The line number
+    pub number: usize,
+    /// The line with the whitespace at either end taken off
+    pub text: &'a str,
+    /// How many characters were taken off the start of the line
+    indent: usize,
+}
+
+impl<'a> Line<'a> {
+    /// The column that the byte at `idx` into `text` is in
+    pub fn column(&self, idx: usize) -> usize {
+        self.indent + self.text[..idx].chars().count() + 1
+    }
+}
+
+/// A run of lines with no blank lines between them.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Group<'a> {
+    lines: Vec<Line<'a>>,
+}
+
+impl<'a> Group<'a> {
+    /// The text of each line in the group
+    pub fn lines(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.lines.iter().map(|line| line.text)
+    }
+
+    /// Each line in the group, with its line number
+    pub fn numbered(&self) -> &[Line<'a>] {
+        &self.lines
+    }
+
+    /// The line number the group starts on
+    pub fn line(&self) -> usize {
+        // groups are never empty
+        self.lines[0].number
+    }
+}
+
+/// Split the input into groups of lines.
+/// Blank lines at the start and end, and extra blank lines between groups, are skipped.
+pub fn groups(input: &str) -> Vec<Group<'_>> {
+    let mut out = Vec::new();
+    let mut current = Vec::new();
+    for (idx, raw) in input.lines().enumerate() {
+        let text = raw.trim();
+        if text.is_empty() {
+            if !current.is_empty() {
+                out.push(Group {
+                    lines: std::mem::take(&mut current),
+                });
+            }
+        } else {
+            let indent = raw.len() - raw.trim_start().len();
+            current.push(Line {
+                number: idx + 1,
+                text,
+                indent: raw[..indent].chars().count(),
+            });
+        }
+    }
+    if !current.is_empty() {
+        out.push(Group { lines: current });
+    }
+    out
+}
+
+/// A `key:value` pair in a record.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Field<'a> {
+    pub key: &'a str,
+    pub value: &'a str,
+    /// Where the key starts
+    pub line: usize,
+    pub column: usize,
+}
+
+/// A group made of whitespace-separated `key:value` pairs.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Record<'a> {
+    /// The line number the record starts on
+    pub line: usize,
+    /// The fields in the order they were in the input
+    pub fields: Vec<Field<'a>>,
+}
+
+impl<'a> Record<'a> {
+    /// Split up a group into its fields
+    pub fn from_group(group: &Group<'a>) -> Result<Self, RecordError> {
+        let mut fields = Vec::new();
+        for line in group.numbered() {
+            let mut rest = line.text;
+            while let Some(start) = rest.find(|c: char| !c.is_whitespace()) {
+                let end = rest[start..]
+                    .find(char::is_whitespace)
+                    .map_or(rest.len(), |len| start + len);
+                let word = &rest[start..end];
+                // where the word is in the whole line
+                let idx = line.text.len() - rest.len() + start;
+                let column = line.column(idx);
+
+                let (key, value) = match word.split_once(':') {
+                    Some((key, value)) if !key.is_empty() => (key, value),
+                    Some(_) => return Err(RecordError::new(line.number, column, "missing a key")),
+                    None => {
+                        return Err(RecordError::new(
+                            line.number,
+                            column,
+                            format!("expected `key:value` but found `{}`", word),
+                        ))
+                    }
+                };
+                fields.push(Field {
+                    key,
+                    value,
+                    line: line.number,
+                    column,
+                });
+                rest = &rest[end..];
+            }
+        }
+        Ok(Self {
+            line: group.line(),
+            fields,
+        })
+    }
+
+    /// Get the first field with this key
+    pub fn get(&self, key: &str) -> Option<&Field<'a>> {
+        self.fields.iter().find(|field| field.key == key)
+    }
+}
+
+/// Split the input into records of `key:value` pairs.
+pub fn records(input: &str) -> Result<Vec<Record<'_>>, RecordError> {
+    groups(input).iter().map(Record::from_group).collect()
+}
+
+/// Something wrong with a record, and where it was.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RecordError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl RecordError {
+    pub fn new(line: usize, column: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    /// An error about the given field
+    pub fn at(field: &Field<'_>, message: impl Into<String>) -> Self {
+        Self::new(field.line, field.column, message)
+    }
+}
+
+impl Display for RecordError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for RecordError {}
+
+#[test]
+fn test_groups() {
+    let input = "\r\nab\r\n  c \r\n\r\n \t\r\n\r\nd\r\ne";
+    let groups = groups(input);
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[0].lines().collect::<Vec<_>>(), vec!["ab", "c"]);
+    assert_eq!(groups[0].line(), 2);
+    assert_eq!(groups[0].numbered()[1].column(0), 3);
+    assert_eq!(groups[1].lines().collect::<Vec<_>>(), vec!["d", "e"]);
+    assert_eq!(groups[1].line(), 7);
+
+    // same thing without the \r's
+    assert_eq!(self::groups(&input.replace('\r', "")), groups);
+    assert!(self::groups("\n \n").is_empty());
+}
+
+#[test]
+fn test_records() {
+    let records = records("a:1 bb:2\r\n  c:x:y\r\n\r\nd:\n").unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(
+        records[0]
+            .fields
+            .iter()
+            .map(|field| (field.key, field.value, field.line, field.column))
+            .collect::<Vec<_>>(),
+        vec![("a", "1", 1, 1), ("bb", "2", 1, 5), ("c", "x:y", 2, 3)]
+    );
+    assert_eq!(records[1].line, 4);
+    assert_eq!(records[1].get("d").map(|field| field.value), Some(""));
+
+    let err = self::records("a:1\nb:2  oops c:3").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "line 2, column 6: expected `key:value` but found `oops`"
+    );
+    let err = self::records("\n\n  :3").unwrap_err();
+    assert_eq!((err.line, err.column), (3, 3));
+}
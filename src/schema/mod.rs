@@ -0,0 +1,632 @@
+//! Checking documents made of `key:value` fields against rules written down in a schema.
+//!
+//! A schema has one field per line: its name, whether it's `required` or `optional`,
+//! and what type it has. A `#` at the start of a word starts a comment:
+//!
+//! ```text
+//! # passports
+//! byr required int 1920..=2002
+//! hgt required measure cm:150..=193 in:59..=76
+//! hcl required regex ^#[0-9a-f]{6}$
+//! ecl required enum amb blu brn gry grn hzl oth
+//! pid required digits 9
+//! cid optional any
+//! ```
+//!
+//! The types are:
+//! - `any`: anything goes
+//! - `int MIN..=MAX`: a whole number; the range is optional, and either end can be left off
+//! - `digits N`: exactly N digits, leading zeroes and all
+//! - `measure UNIT:MIN..=MAX ...`: a whole number followed by one of the units,
+//!   and in that unit's range
+//! - `regex PATTERN`: matches the pattern, which is the rest of the line
+//! - `enum A B C ...`: one of the words
+//!
+//! Fields that aren't in the schema aren't allowed, unless there's a line for `*`,
+//! in which case they have to be of its type.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{Display, Formatter},
+    fs,
+    path::Path,
+    str::FromStr,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use regex::Regex;
+
+/// Something with named text fields that a schema can check.
+pub trait Document {
+    /// Get the value of a field, if it's there
+    fn get(&self, key: &str) -> Option<&str>;
+    /// The names of all the fields that are there
+    fn keys(&self) -> Vec<&str>;
+}
+
+impl Document for HashMap<String, String> {
+    fn get(&self, key: &str) -> Option<&str> {
+        HashMap::get(self, key).map(String::as_str)
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        HashMap::keys(self).map(String::as_str).collect()
+    }
+}
+
+impl Document for BTreeMap<String, String> {
+    fn get(&self, key: &str) -> Option<&str> {
+        BTreeMap::get(self, key).map(String::as_str)
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        BTreeMap::keys(self).map(String::as_str).collect()
+    }
+}
+
+/// An inclusive range where either end might be missing
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Bounds {
+    pub min: Option<i64>,
+    pub max: Option<i64>,
+}
+
+impl Bounds {
+    /// Check a number is in range, explaining why not if it isn't.
+    /// `unit` goes after the number in the explanation.
+    fn check(&self, num: i64, unit: &str) -> Result<(), String> {
+        match (self.min, self.max) {
+            (Some(min), _) if num < min => Err(format!("{}{} is below {}", num, unit, min)),
+            (_, Some(max)) if num > max => Err(format!("{}{} exceeds {}", num, unit, max)),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl FromStr for Bounds {
+    type Err = anyhow::Error;
+    /// Parse `MIN..=MAX`, `MIN..` or `..=MAX`
+    fn from_str(s: &str) -> Result<Self> {
+        let (min, max) = match s.split_once("..") {
+            Some(it) => it,
+            None => bail!("expected a range like `1..=5`, found `{}`", s),
+        };
+        let max = match max {
+            "" => None,
+            max => Some(
+                max.strip_prefix('=')
+                    .ok_or_else(|| anyhow!("ranges have to be inclusive, like `1..=5`"))?
+                    .parse()
+                    .with_context(|| format!("bad maximum in `{}`", s))?,
+            ),
+        };
+        let min = match min {
+            "" => None,
+            min => Some(
+                min.parse()
+                    .with_context(|| format!("bad minimum in `{}`", s))?,
+            ),
+        };
+        Ok(Self { min, max })
+    }
+}
+
+impl Display for Bounds {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(min) = self.min {
+            write!(f, "{}", min)?;
+        }
+        f.write_str("..")?;
+        if let Some(max) = self.max {
+            write!(f, "={}", max)?;
+        }
+        Ok(())
+    }
+}
+
+/// What a field's value has to look like
+#[derive(Debug, Clone)]
+pub enum FieldType {
+    Any,
+    Int(Option<Bounds>),
+    Digits(usize),
+    /// The units, each with its range
+    Measure(Vec<(String, Bounds)>),
+    Regex(Regex),
+    Enum(Vec<String>),
+}
+
+impl FieldType {
+    /// Check a value is of this type, explaining why not if it isn't.
+    pub fn check(&self, value: &str) -> Result<(), String> {
+        match self {
+            FieldType::Any => Ok(()),
+            FieldType::Int(bounds) => {
+                let num = value
+                    .parse()
+                    .map_err(|_| format!("`{}` isn't a number", value))?;
+                match bounds {
+                    Some(bounds) => bounds.check(num, ""),
+                    None => Ok(()),
+                }
+            }
+            FieldType::Digits(len) => {
+                if !value.chars().all(|c| c.is_ascii_digit()) {
+                    Err(format!("`{}` isn't all digits", value))
+                } else if value.len() != *len {
+                    Err(format!("{} digits, expected {}", value.len(), len))
+                } else {
+                    Ok(())
+                }
+            }
+            FieldType::Measure(units) => {
+                let unit_idx = value
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(value.len());
+                let (num, unit) = value.split_at(unit_idx);
+                let unit_names = || {
+                    units
+                        .iter()
+                        .map(|(name, _)| name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                };
+                let bounds = match units.iter().find(|(name, _)| name == unit) {
+                    Some((_, bounds)) => bounds,
+                    None if unit.is_empty() => {
+                        return Err(format!(
+                            "`{}` has no unit, expected one of {}",
+                            value,
+                            unit_names()
+                        ))
+                    }
+                    None => {
+                        return Err(format!(
+                            "unknown unit `{}`, expected one of {}",
+                            unit,
+                            unit_names()
+                        ))
+                    }
+                };
+                let num = num
+                    .parse()
+                    .map_err(|_| format!("`{}` doesn't start with a number", value))?;
+                bounds.check(num, unit)
+            }
+            FieldType::Regex(regex) => {
+                if regex.is_match(value) {
+                    Ok(())
+                } else {
+                    Err(format!("`{}` doesn't match `{}`", value, regex))
+                }
+            }
+            FieldType::Enum(options) => {
+                if options.iter().any(|it| it == value) {
+                    Ok(())
+                } else {
+                    Err(format!("`{}` isn't one of {}", value, options.join(", ")))
+                }
+            }
+        }
+    }
+
+    /// Parse a type from its name and the rest of the line
+    fn parse(name: &str, args: &str) -> Result<Self> {
+        let words = args.split_whitespace().collect::<Vec<_>>();
+        Ok(match name {
+            "any" => FieldType::Any,
+            "int" => match words.as_slice() {
+                [] => FieldType::Int(None),
+                [bounds] => FieldType::Int(Some(bounds.parse()?)),
+                _ => bail!("`int` takes at most one range"),
+            },
+            "digits" => match words.as_slice() {
+                [len] => FieldType::Digits(
+                    len.parse()
+                        .with_context(|| format!("bad length `{}`", len))?,
+                ),
+                _ => bail!("`digits` takes one length"),
+            },
+            "measure" => {
+                if words.is_empty() {
+                    bail!("`measure` needs at least one unit");
+                }
+                FieldType::Measure(
+                    words
+                        .iter()
+                        .map(|word| {
+                            let (unit, bounds) = word.split_once(':').ok_or_else(|| {
+                                anyhow!("expected a unit like `cm:150..=193`, found `{}`", word)
+                            })?;
+                            Ok((unit.to_string(), bounds.parse()?))
+                        })
+                        .collect::<Result<_>>()?,
+                )
+            }
+            "regex" => FieldType::Regex(Regex::new(args.trim())?),
+            "enum" => {
+                if words.is_empty() {
+                    bail!("`enum` needs at least one option");
+                }
+                FieldType::Enum(words.iter().map(|it| it.to_string()).collect())
+            }
+            oh_no => bail!("unknown type `{}`", oh_no),
+        })
+    }
+}
+
+/// One line of a schema
+#[derive(Debug, Clone)]
+pub struct FieldRule {
+    pub name: String,
+    pub required: bool,
+    pub ty: FieldType,
+}
+
+#[derive(Debug, Clone)]
+pub struct Schema {
+    /// In the order they're written
+    fields: Vec<FieldRule>,
+    /// What fields that aren't listed have to be, if they're allowed at all
+    other: Option<FieldType>,
+}
+
+impl Schema {
+    /// Load a schema from a file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        fs::read_to_string(path)
+            .with_context(|| format!("couldn't read schema {}", path.display()))?
+            .parse()
+            .with_context(|| format!("in schema {}", path.display()))
+    }
+
+    /// The fields, in the order they're written
+    pub fn fields(&self) -> &[FieldRule] {
+        &self.fields
+    }
+
+    /// Get the rule for a field
+    pub fn field(&self, name: &str) -> Option<&FieldRule> {
+        self.fields.iter().find(|rule| rule.name == name)
+    }
+
+    /// The required fields that aren't there, without looking at any values
+    pub fn missing(&self, doc: &dyn Document) -> Vec<&str> {
+        self.fields
+            .iter()
+            .filter(|rule| rule.required && doc.get(&rule.name).is_none())
+            .map(|rule| rule.name.as_str())
+            .collect()
+    }
+
+    /// Check a document, erroring with the first problem.
+    /// See [`Schema::report`] for the order things are checked in.
+    pub fn validate(&self, doc: &dyn Document) -> Result<()> {
+        match self.report(doc).errors.into_iter().next() {
+            Some(err) => Err(anyhow!("{}", err)),
+            None => Ok(()),
+        }
+    }
+
+    /// Check everything in a document, listing every problem with it.
+    /// Fields are checked in the order they're written, and then any that aren't in the schema.
+    pub fn report(&self, doc: &dyn Document) -> Report {
+        let mut errors = Vec::new();
+        let mut fail = |field: &str, reason: String| {
+            errors.push(FieldError {
+                field: field.to_string(),
+                reason,
+            })
+        };
+
+        for rule in self.fields.iter() {
+            match doc.get(&rule.name) {
+                Some(value) => {
+                    if let Err(reason) = rule.ty.check(value) {
+                        fail(&rule.name, reason);
+                    }
+                }
+                None if rule.required => fail(&rule.name, String::from("missing")),
+                None => {}
+            }
+        }
+
+        let mut keys = doc.keys();
+        keys.sort_unstable();
+        for key in keys {
+            if self.field(key).is_some() {
+                continue;
+            }
+            let checked = match &self.other {
+                Some(ty) => ty.check(doc.get(key).unwrap_or_default()),
+                None => Err(String::from("not in the schema")),
+            };
+            if let Err(reason) = checked {
+                fail(key, reason);
+            }
+        }
+        Report { errors }
+    }
+
+    /// Check a whole bunch of documents, counting up what went wrong with them.
+    pub fn batch_report<'a, D: Document + 'a>(
+        &self,
+        docs: impl IntoIterator<Item = &'a D>,
+    ) -> BatchReport {
+        let mut batch = BatchReport::default();
+        for doc in docs {
+            batch.add(&self.report(doc));
+        }
+        batch
+    }
+}
+
+/// Something wrong with one field of a document
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FieldError {
+    pub field: String,
+    pub reason: String,
+}
+
+impl Display for FieldError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.field, self.reason)
+    }
+}
+
+/// Everything wrong with a document
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Report {
+    pub errors: Vec<FieldError>,
+}
+
+impl Report {
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Did this field have something wrong with it?
+    pub fn failed(&self, field: &str) -> bool {
+        self.errors.iter().any(|err| err.field == field)
+    }
+}
+
+impl Display for Report {
+    /// Writes one error per line, or `valid` if there aren't any
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.errors.is_empty() {
+            return f.write_str("valid");
+        }
+        for (idx, err) in self.errors.iter().enumerate() {
+            if idx != 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", err)?;
+        }
+        Ok(())
+    }
+}
+
+/// Counts of what went wrong across lots of documents
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct BatchReport {
+    pub total: usize,
+    pub valid: usize,
+    /// How many documents each field was wrong in
+    pub failures: BTreeMap<String, usize>,
+}
+
+impl BatchReport {
+    /// Count up another document's report
+    pub fn add(&mut self, report: &Report) {
+        self.total += 1;
+        if report.is_valid() {
+            self.valid += 1;
+        }
+        let mut fields = report
+            .errors
+            .iter()
+            .map(|err| err.field.as_str())
+            .collect::<Vec<_>>();
+        // a field only counts once per document
+        fields.sort_unstable();
+        fields.dedup();
+        for field in fields {
+            *self.failures.entry(field.to_string()).or_default() += 1;
+        }
+    }
+}
+
+impl Display for BatchReport {
+    /// Writes the totals, then the fields that failed most first
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} of {} valid", self.valid, self.total)?;
+        let mut failures = self.failures.iter().collect::<Vec<_>>();
+        failures.sort_by_key(|&(field, count)| (std::cmp::Reverse(count), field));
+        for (field, count) in failures {
+            write!(f, "\n  {}: {} failed", field, count)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Schema {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self> {
+        let mut fields: Vec<FieldRule> = Vec::new();
+        let mut other = None;
+        for (idx, line) in s.lines().enumerate() {
+            // regexes can have #s in them, so only a # at the start of a word is a comment
+            let line = match line
+                .match_indices('#')
+                .find(|&(hash, _)| hash == 0 || line[..hash].ends_with(char::is_whitespace))
+            {
+                Some((hash, _)) => &line[..hash],
+                None => line,
+            };
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let mut parse_line = || -> Result<()> {
+                let mut rest = line;
+                let name = next_word(&mut rest).unwrap_or_default();
+                let required = match next_word(&mut rest) {
+                    Some("required") => true,
+                    Some("optional") => false,
+                    Some(oh_no) => bail!("expected `required` or `optional`, found `{}`", oh_no),
+                    None => bail!("expected `required` or `optional` after `{}`", name),
+                };
+                let ty = match next_word(&mut rest) {
+                    Some(ty) => FieldType::parse(ty, rest.trim())?,
+                    None => bail!("`{}` needs a type", name),
+                };
+
+                if name == "*" {
+                    if required {
+                        bail!("`*` can't be required");
+                    }
+                    if other.replace(ty).is_some() {
+                        bail!("`*` is already in the schema");
+                    }
+                } else {
+                    if fields.iter().any(|rule| rule.name == name) {
+                        bail!("`{}` is already in the schema", name);
+                    }
+                    fields.push(FieldRule {
+                        name: name.to_string(),
+                        required,
+                        ty,
+                    });
+                }
+                Ok(())
+            };
+            parse_line().with_context(|| format!("on line {}", idx + 1))?;
+        }
+        Ok(Self { fields, other })
+    }
+}
+
+/// Take the next whitespace-separated word off the front of `s`
+fn next_word<'a>(s: &mut &'a str) -> Option<&'a str> {
+    let trimmed = s.trim_start();
+    let end = trimmed.find(char::is_whitespace).unwrap_or(trimmed.len());
+    let (word, rest) = trimmed.split_at(end);
+    *s = rest;
+    if word.is_empty() {
+        None
+    } else {
+        Some(word)
+    }
+}
+
+#[test]
+fn test_schema() -> Result<()> {
+    let schema = r"
+# a comment
+name required regex ^[A-Z][a-z]+$  # another comment
+color required regex ^#[0-9a-f]{6}$
+age required int 0..=150
+size optional measure cm:1..=300 in:1..=120
+id required digits 4
+pet optional enum cat dog
+"
+    .parse::<Schema>()?;
+    assert_eq!(schema.fields().len(), 6);
+
+    let doc = |fields: &[(&str, &str)]| -> BTreeMap<String, String> {
+        fields
+            .iter()
+            .map(|&(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    };
+    let good = doc(&[
+        ("name", "Jo"),
+        ("color", "#00ff00"),
+        ("age", "30"),
+        ("id", "0042"),
+        ("pet", "cat"),
+    ]);
+    schema.validate(&good)?;
+    assert!(schema.missing(&good).is_empty());
+
+    let err = |fields: &[(&str, &str)]| {
+        let mut it = good.clone();
+        it.extend(doc(fields));
+        schema.validate(&it).unwrap_err().to_string()
+    };
+    assert_eq!(err(&[("age", "151")]), "age: 151 exceeds 150");
+    assert_eq!(err(&[("age", "old")]), "age: `old` isn't a number");
+    assert_eq!(err(&[("size", "400cm")]), "size: 400cm exceeds 300");
+    assert_eq!(err(&[("size", "0in")]), "size: 0in is below 1");
+    assert_eq!(
+        err(&[("size", "3ft")]),
+        "size: unknown unit `ft`, expected one of cm, in"
+    );
+    assert_eq!(
+        err(&[("size", "30")]),
+        "size: `30` has no unit, expected one of cm, in"
+    );
+    assert_eq!(err(&[("id", "12345")]), "id: 5 digits, expected 4");
+    assert_eq!(err(&[("id", "12a4")]), "id: `12a4` isn't all digits");
+    assert_eq!(err(&[("pet", "fish")]), "pet: `fish` isn't one of cat, dog");
+    assert_eq!(
+        err(&[("color", "#00ff0")]),
+        "color: `#00ff0` doesn't match `^#[0-9a-f]{6}$`"
+    );
+    assert_eq!(err(&[("zzz", "1")]), "zzz: not in the schema");
+
+    let mut bad = good.clone();
+    bad.remove("age");
+    bad.remove("id");
+    assert_eq!(schema.missing(&bad), vec!["age", "id"]);
+    assert_eq!(
+        schema.validate(&bad).unwrap_err().to_string(),
+        "age: missing"
+    );
+
+    bad.insert(String::from("pet"), String::from("fish"));
+    let report = schema.report(&bad);
+    assert_eq!(
+        report.to_string(),
+        "age: missing\nid: missing\npet: `fish` isn't one of cat, dog"
+    );
+    assert!(report.failed("pet") && !report.failed("name"));
+    assert_eq!(schema.report(&good).to_string(), "valid");
+
+    let batch = schema.batch_report(vec![&good, &bad, &good]);
+    assert_eq!((batch.total, batch.valid), (3, 2));
+    assert_eq!(batch.failures.get("age"), Some(&1));
+    assert_eq!(batch.failures.get("name"), None);
+
+    // lined-up columns and comments after a regex with a # in it
+    let aligned =
+        "byr  required  int    1920..=2002\nhcl  required  regex  ^#[0-9a-f]{6}$  # colour\n"
+            .parse::<Schema>()?;
+    aligned.validate(&doc(&[("byr", "1950"), ("hcl", "#123abc")]))?;
+    assert!(aligned
+        .validate(&doc(&[("byr", "1950"), ("hcl", "#123abg")]))
+        .is_err());
+
+    let open = "* optional int ..=9".parse::<Schema>()?;
+    open.validate(&doc(&[("a", "1"), ("b", "-5")]))?;
+    assert!(open.validate(&doc(&[("a", "10")])).is_err());
+
+    for bad in &[
+        "x maybe any",
+        "x required",
+        "x required float",
+        "x required int 1..5",
+        "x required int 1..=5 6..=7",
+        "x required measure cm",
+        "x required enum",
+        "x required any\nx optional any",
+        "* required any",
+    ] {
+        assert!(bad.parse::<Schema>().is_err(), "{}", bad);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,232 @@
+//! A common interface for every day's solution,
+//! and a registry of them all so they can be looked up by day number.
+
+use anyhow::{anyhow, bail, Error, Result};
+
+use std::{any::Any, fmt::Display, str::FromStr};
+
+use crate::input::{ExtraFiles, InputLoader};
+use crate::{
+    day1::Day1, day10::Day10, day11::Day11, day12::Day12, day13::Day13, day14::Day14, day15::Day15,
+    day16::Day16, day17::Day17, day18::Day18, day2::Day2, day3::Day3, day4::Day4, day5::Day5,
+    day6::Day6, day7::Day7, day8::Day8, day9::Day9,
+};
+
+/// A solution to one day's puzzle.
+///
+/// The input gets parsed once, and then both parts are solved from the parsed form.
+pub trait Solution {
+    /// What the input turns into after parsing.
+    type Parsed;
+    /// The answer to part 1.
+    type Answer1: Display;
+    /// The answer to part 2.
+    type Answer2: Display;
+
+    /// Other files next to the input that parsing needs, like day 4's `passport.schema`.
+    /// The runner loads them once up front, so reading them isn't counted as parsing.
+    const EXTRA_FILES: &'static [&'static str] = &[];
+
+    /// Parse the puzzle input.
+    fn parse(input: &str) -> Result<Self::Parsed>;
+    /// Parse the puzzle input along with whichever of `EXTRA_FILES` were found.
+    /// Most days don't need any, so by default this is just `parse`.
+    fn parse_with(input: &str, _extra: &ExtraFiles) -> Result<Self::Parsed> {
+        Self::parse(input)
+    }
+    /// Solve part 1.
+    fn part1(parsed: &Self::Parsed) -> Result<Self::Answer1>;
+    /// Solve part 2.
+    fn part2(parsed: &Self::Parsed) -> Result<Self::Answer2>;
+}
+
+/// One of the two parts of a day.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    /// Both parts, in order.
+    pub const ALL: [Part; 2] = [Part::One, Part::Two];
+
+    /// Get the number of this part
+    pub fn number(self) -> u32 {
+        match self {
+            Part::One => 1,
+            Part::Two => 2,
+        }
+    }
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.number())
+    }
+}
+
+impl FromStr for Part {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "1" => Part::One,
+            "2" => Part::Two,
+            oh_no => bail!("unknown part `{}`, expected 1 or 2", oh_no),
+        })
+    }
+}
+
+/// A parsed input, with the type erased so any day's can be passed around.
+pub struct ParsedInput {
+    day: u32,
+    inner: Box<dyn Any>,
+}
+
+/// A day's solution in the registry.
+pub struct Entry {
+    /// The day this solves
+    pub day: u32,
+    extra_files: &'static [&'static str],
+    parse: fn(&str, &ExtraFiles) -> Result<Box<dyn Any>>,
+    part1: fn(&dyn Any) -> Result<String>,
+    part2: fn(&dyn Any) -> Result<String>,
+}
+
+impl Entry {
+    /// Make an entry for a Solution
+    const fn new<S: Solution>(day: u32) -> Self
+    where
+        S::Parsed: 'static,
+    {
+        Self {
+            day,
+            extra_files: S::EXTRA_FILES,
+            parse: parse_erased::<S>,
+            part1: part1_erased::<S>,
+            part2: part2_erased::<S>,
+        }
+    }
+
+    /// Load the other files this day needs from next to its input.
+    pub fn load_extra(&self, loader: &InputLoader) -> Result<ExtraFiles> {
+        loader.load_extra(self.day, self.extra_files)
+    }
+
+    /// Parse an input for this day, without any other files.
+    pub fn parse(&self, input: &str) -> Result<ParsedInput> {
+        self.parse_with(input, &ExtraFiles::default())
+    }
+
+    /// Parse an input for this day, with the other files from `load_extra`.
+    pub fn parse_with(&self, input: &str, extra: &ExtraFiles) -> Result<ParsedInput> {
+        let inner = (self.parse)(input, extra)?;
+        Ok(ParsedInput {
+            day: self.day,
+            inner,
+        })
+    }
+
+    /// Solve one part from an input that was already parsed by this entry.
+    pub fn solve_parsed(&self, parsed: &ParsedInput, part: Part) -> Result<String> {
+        if parsed.day != self.day {
+            bail!(
+                "tried to solve day {} with input parsed for day {}",
+                self.day,
+                parsed.day
+            );
+        }
+        match part {
+            Part::One => (self.part1)(parsed.inner.as_ref()),
+            Part::Two => (self.part2)(parsed.inner.as_ref()),
+        }
+    }
+
+    /// Parse the input and solve one part of it.
+    pub fn solve(&self, part: Part, input: &str) -> Result<String> {
+        let parsed = self.parse(input)?;
+        self.solve_parsed(&parsed, part)
+    }
+}
+
+fn parse_erased<S: Solution>(input: &str, extra: &ExtraFiles) -> Result<Box<dyn Any>>
+where
+    S::Parsed: 'static,
+{
+    Ok(Box::new(S::parse_with(input, extra)?))
+}
+
+fn part1_erased<S: Solution>(parsed: &dyn Any) -> Result<String>
+where
+    S::Parsed: 'static,
+{
+    let parsed = downcast::<S>(parsed)?;
+    Ok(S::part1(parsed)?.to_string())
+}
+
+fn part2_erased<S: Solution>(parsed: &dyn Any) -> Result<String>
+where
+    S::Parsed: 'static,
+{
+    let parsed = downcast::<S>(parsed)?;
+    Ok(S::part2(parsed)?.to_string())
+}
+
+fn downcast<S: Solution>(parsed: &dyn Any) -> Result<&S::Parsed>
+where
+    S::Parsed: 'static,
+{
+    parsed
+        .downcast_ref()
+        .ok_or_else(|| anyhow!("parsed input was the wrong type"))
+}
+
+/// Every day with a solution, in order.
+pub static REGISTRY: &[Entry] = &[
+    Entry::new::<Day1>(1),
+    Entry::new::<Day2>(2),
+    Entry::new::<Day3>(3),
+    Entry::new::<Day4>(4),
+    Entry::new::<Day5>(5),
+    Entry::new::<Day6>(6),
+    Entry::new::<Day7>(7),
+    Entry::new::<Day8>(8),
+    Entry::new::<Day9>(9),
+    Entry::new::<Day10>(10),
+    Entry::new::<Day11>(11),
+    Entry::new::<Day12>(12),
+    Entry::new::<Day13>(13),
+    Entry::new::<Day14>(14),
+    Entry::new::<Day15>(15),
+    Entry::new::<Day16>(16),
+    Entry::new::<Day17>(17),
+    Entry::new::<Day18>(18),
+];
+
+/// Get the entry for a day, if there is one.
+pub fn get(day: u32) -> Option<&'static Entry> {
+    REGISTRY.iter().find(|entry| entry.day == day)
+}
+
+/// Solve one part of a day with the given input.
+pub fn solve(day: u32, part: Part, input: &str) -> Result<String> {
+    let entry = get(day).ok_or_else(|| anyhow!("there's no solution for day {}", day))?;
+    entry.solve(part, input)
+}
+
+#[test]
+fn test_registry() -> Result<()> {
+    for (idx, entry) in REGISTRY.iter().enumerate() {
+        assert_eq!(entry.day, idx as u32 + 1);
+    }
+    assert!(get(25).is_none());
+    assert!(solve(25, Part::One, "").is_err());
+
+    assert_eq!(solve(15, Part::One, "0,3,6")?, "436");
+    assert_eq!(solve(5, Part::One, "FBFBBFFRLR\nBFFFBBFRRR")?, "567");
+
+    let parsed = get(15).unwrap().parse("0,3,6")?;
+    assert!(get(5).unwrap().solve_parsed(&parsed, Part::One).is_err());
+
+    Ok(())
+}